@@ -1,4 +1,5 @@
 use binary_tree_collections::map::BinaryMap;
+use binary_tree_collections::pair_map::BinaryPairMap;
 use cc_traits::Len;
 use criterion::{BenchmarkId, Criterion};
 use hashbrown::hash_map::DefaultHashBuilder;
@@ -21,7 +22,7 @@ fn bench_maps_from_iter<TKey: Clone + Eq + Hash + Ord>(
     let mut group = c.benchmark_group(name);
 
     for n in [1u64, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096] {
-        group.throughput(criterion::Throughput::Elements(n as u64));
+        group.throughput(criterion::Throughput::Elements(n));
         let pairs: Vec<_> = (0..n).map(|v| v * 2).map(|v| (make_key(v), v)).collect();
 
         group.bench_function(BenchmarkId::new("Hashmap", n), |b| {
@@ -33,6 +34,9 @@ fn bench_maps_from_iter<TKey: Clone + Eq + Hash + Ord>(
         group.bench_function(BenchmarkId::new("BinaryMap", n), |b| {
             b.iter(|| bench_map_from_iter::<TKey, u64, BinaryMap<_, _>>(&pairs))
         });
+        group.bench_function(BenchmarkId::new("BinaryPairMap", n), |b| {
+            b.iter(|| bench_map_from_iter::<TKey, u64, BinaryPairMap<_, _>>(&pairs))
+        });
         
         
         group.bench_function(BenchmarkId::new("Hashbrown HashMap", n), |b| {
@@ -1,4 +1,6 @@
+use binary_tree_collections::eytzinger::EytzingerMap;
 use binary_tree_collections::map::BinaryMap;
+use binary_tree_collections::pair_map::BinaryPairMap;
 use cc_traits::Map;
 use criterion::{BenchmarkId, Criterion};
 use std::collections::{BTreeMap, HashMap};
@@ -20,14 +22,16 @@ fn bench_maps_get<TKey: Clone + Eq + Hash + Ord>(
     let mut group = c.benchmark_group(name);
 
     for n in [1u64, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096] {
-        group.throughput(criterion::Throughput::Elements(n as u64));
+        group.throughput(criterion::Throughput::Elements(n));
         let pairs: Vec<_> = (0..n).map(|v| v * 2).map(|v| (make_key(v), v)).collect();
 
-        let keys_to_search: Vec<TKey> = (0..(n * 2)).map(|v| make_key(v)).collect();
+        let keys_to_search: Vec<TKey> = (0..(n * 2)).map(&make_key).collect();
 
         let hash_map: HashMap<TKey, u64> = HashMap::from_iter(pairs.iter().cloned());
         let btree_map: BTreeMap<TKey, u64> = BTreeMap::from_iter(pairs.iter().cloned());
         let binary_map: BinaryMap<TKey, u64> = BinaryMap::from_iter(pairs.iter().cloned());
+        let pair_map: BinaryPairMap<TKey, u64> = BinaryPairMap::from(binary_map.clone());
+        let eytzinger_map: EytzingerMap<TKey, u64> = EytzingerMap::from(binary_map.clone());
         let hashbrown_map: hashbrown::HashMap<TKey, u64> = hashbrown::HashMap::from_iter(pairs.iter().cloned());
 
         group.bench_function(BenchmarkId::new("Hashmap", n), |b| {
@@ -39,6 +43,18 @@ fn bench_maps_get<TKey: Clone + Eq + Hash + Ord>(
         group.bench_function(BenchmarkId::new("BinaryMap", n), |b| {
             b.iter(|| bench_map_get(&binary_map, &keys_to_search))
         });
+        group.bench_function(BenchmarkId::new("BinaryPairMap", n), |b| {
+            b.iter(|| bench_map_get(&pair_map, &keys_to_search))
+        });
+        group.bench_function(BenchmarkId::new("BinaryMap get_key_value", n), |b| {
+            b.iter(|| bench_map_get_key_value(&binary_map, &keys_to_search))
+        });
+        group.bench_function(BenchmarkId::new("BinaryPairMap get_key_value", n), |b| {
+            b.iter(|| bench_map_get_key_value(&pair_map, &keys_to_search))
+        });
+        group.bench_function(BenchmarkId::new("EytzingerMap", n), |b| {
+            b.iter(|| bench_map_get(&eytzinger_map, &keys_to_search))
+        });
         
         group.bench_function(BenchmarkId::new("Hashbrown", n), |b| {
             b.iter(|| bench_hashbrown_get(&hashbrown_map, &keys_to_search))
@@ -55,8 +71,18 @@ fn bench_map_get<'a, TKey, TMap: 'a + Map<TKey, u64, ItemRef<'a> = &'a u64>>(
     keys.iter().flat_map(|key| map.get(key)).cloned().sum()
 }
 
-fn bench_hashbrown_get<'a, TKey : Eq +Hash>(
-    map: &'a hashbrown::HashMap<TKey, u64>,
+fn bench_map_get_key_value<'a, TKey: 'a, TMap>(map: &'a TMap, keys: &[TKey]) -> u64
+where
+    TMap: 'a + Map<TKey, u64, ItemRef<'a> = &'a u64, KeyRef<'a> = &'a TKey>,
+{
+    keys.iter()
+        .flat_map(|key| map.get_key_value(key))
+        .map(|(_, v)| *v)
+        .sum()
+}
+
+fn bench_hashbrown_get<TKey : Eq +Hash>(
+    map: &hashbrown::HashMap<TKey, u64>,
     keys: &[TKey],
 ) -> u64 {
     keys.iter().flat_map(|key| map.get(key)).cloned().sum()
@@ -1,4 +1,5 @@
 use binary_tree_collections::map::BinaryMap;
+use binary_tree_collections::pair_map::BinaryPairMap;
 use criterion::{BenchmarkId, Criterion};
 use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
@@ -19,13 +20,14 @@ fn bench_maps_sum_keys<TKey: Clone + Eq + Hash + Ord + Sum>(
     let mut group = c.benchmark_group(name);
 
     for n in [1u64, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096] {
-        group.throughput(criterion::Throughput::Elements(n as u64));
+        group.throughput(criterion::Throughput::Elements(n));
         let pairs: Vec<_> = (0..n).map(|v| v * 2).map(|v| (make_key(v), v)).collect();
 
 
         let hash_map: HashMap<TKey, u64> = HashMap::from_iter(pairs.iter().cloned());
         let btree_map: BTreeMap<TKey, u64> = BTreeMap::from_iter(pairs.iter().cloned());
         let binary_map: BinaryMap<TKey, u64> = BinaryMap::from_iter(pairs.iter().cloned());
+        let pair_map: BinaryPairMap<TKey, u64> = BinaryPairMap::from_iter(pairs.iter().cloned());
         let hashbrown_map: hashbrown::HashMap<TKey, u64> = hashbrown::HashMap::from_iter(pairs.iter().cloned());
 
         group.bench_function(BenchmarkId::new("Hashmap", n), |b| {
@@ -37,6 +39,9 @@ fn bench_maps_sum_keys<TKey: Clone + Eq + Hash + Ord + Sum>(
         group.bench_function(BenchmarkId::new("BinaryMap", n), |b| {
             b.iter(|| binary_map_sum_keys(&binary_map))
         });
+        group.bench_function(BenchmarkId::new("BinaryPairMap", n), |b| {
+            b.iter(|| pair_map_sum_keys(&pair_map))
+        });
         
         group.bench_function(BenchmarkId::new("Hashbrown", n), |b| {
             b.iter(|| hashbrown_map_sum_keys(&hashbrown_map))
@@ -54,6 +59,10 @@ fn binary_map_sum_keys<TKey : Sum + Clone, TValue>(map: &BinaryMap<TKey, TValue>
     map.keys().iter().cloned().sum()
 }
 
+fn pair_map_sum_keys<TKey: Sum + Clone, TValue>(map: &BinaryPairMap<TKey, TValue>) -> TKey {
+    map.keys().cloned().sum()
+}
+
 fn hashbrown_map_sum_keys<TKey : Sum + Clone, TValue>(map: &hashbrown::HashMap<TKey, TValue>)-> TKey{
     map.keys().cloned().sum()
 }
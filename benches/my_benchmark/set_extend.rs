@@ -25,6 +25,16 @@ fn extend(set: &mut BinarySet<usize>, vec: &[usize]) -> usize {
     set.len()
 }
 
+fn extend_with_sort(set: &mut BinarySet<usize>, vec: &[usize]) -> usize {
+    set.extend_with_sort(vec.iter().cloned());
+    set.len()
+}
+
+fn extend_with_merge(set: &mut BinarySet<usize>, vec: &[usize]) -> usize {
+    set.extend_with_merge(vec.iter().cloned());
+    set.len()
+}
+
 fn extend_with_inserts(set: &mut BinarySet<usize>, vec: &[usize]) -> usize {
     set.extend_with_inserts(vec.iter().cloned());
     set.len()
@@ -52,6 +62,20 @@ fn bench_extend(
                 criterion::BatchSize::SmallInput,
             )
         });
+        group.bench_function(BenchmarkId::new("extend with sort", n), |b| {
+            b.iter_batched_ref(
+                || args.0.clone(),
+                |a| extend_with_sort(a, &args.1),
+                criterion::BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("extend with merge", n), |b| {
+            b.iter_batched_ref(
+                || args.0.clone(),
+                |a| extend_with_merge(a, &args.1),
+                criterion::BatchSize::SmallInput,
+            )
+        });
         group.bench_function(BenchmarkId::new("extend with inserts", n), |b| {
             b.iter_batched_ref(
                 || args.0.clone(),
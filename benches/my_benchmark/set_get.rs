@@ -0,0 +1,58 @@
+use binary_tree_collections::eytzinger::EytzingerSet;
+use binary_tree_collections::set::BinarySet;
+use binary_tree_collections::static_btree::{NodeKey, StaticBTreeSet};
+use cc_traits::Set;
+use criterion::{BenchmarkId, Criterion};
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+
+pub fn set_get_benchmark(c: &mut Criterion) {
+    bench_sets_get::<u64>(c, "U64 Sets Get", |x| x);
+    bench_sets_get::<u32>(c, "U32 Sets Get", |x| x as u32);
+}
+
+fn bench_sets_get<TKey: Clone + Eq + Hash + NodeKey>(
+    c: &mut Criterion,
+    name: &str,
+    make_key: impl Fn(u64) -> TKey,
+) {
+    let mut group = c.benchmark_group(name);
+
+    for n in [16u64, 256, 4096, 65536, 1 << 20] {
+        let keys: Vec<TKey> = (0..n).map(|v| v * 2).map(&make_key).collect();
+        // a fixed scatter of hits and misses, so the searches are not predictable
+        let keys_to_search: Vec<TKey> = (0..1024u64)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15) % (n * 2))
+            .map(&make_key)
+            .collect();
+        group.throughput(criterion::Throughput::Elements(keys_to_search.len() as u64));
+
+        let hash_set: HashSet<TKey> = HashSet::from_iter(keys.iter().cloned());
+        let btree_set: BTreeSet<TKey> = BTreeSet::from_iter(keys.iter().cloned());
+        let binary_set: BinarySet<TKey> = BinarySet::from_iter(keys.iter().cloned());
+        let eytzinger_set: EytzingerSet<TKey> = EytzingerSet::from(binary_set.clone());
+        let static_btree_set: StaticBTreeSet<TKey> = StaticBTreeSet::from(binary_set.clone());
+
+        group.bench_function(BenchmarkId::new("HashSet", n), |b| {
+            b.iter(|| bench_set_get(&hash_set, &keys_to_search))
+        });
+        group.bench_function(BenchmarkId::new("BTreeSet", n), |b| {
+            b.iter(|| bench_set_get(&btree_set, &keys_to_search))
+        });
+        group.bench_function(BenchmarkId::new("BinarySet", n), |b| {
+            b.iter(|| bench_set_get(&binary_set, &keys_to_search))
+        });
+        group.bench_function(BenchmarkId::new("EytzingerSet", n), |b| {
+            b.iter(|| bench_set_get(&eytzinger_set, &keys_to_search))
+        });
+        group.bench_function(BenchmarkId::new("StaticBTreeSet", n), |b| {
+            b.iter(|| bench_set_get(&static_btree_set, &keys_to_search))
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_set_get<TKey, TSet: Set<TKey>>(set: &TSet, keys: &[TKey]) -> usize {
+    keys.iter().filter(|key| set.contains(key)).count()
+}
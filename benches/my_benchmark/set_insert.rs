@@ -0,0 +1,50 @@
+use binary_tree_collections::chunked::ChunkedBinarySet;
+use binary_tree_collections::set::BinarySet;
+use cc_traits::SetMut;
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use std::collections::BTreeSet;
+
+pub fn set_insert_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("U64 Sets Insert");
+
+    for n in [1024u64, 65536, 1 << 20] {
+        let keys: Vec<u64> = (0..n).map(|v| v * 2).collect();
+        // odd keys scattered over the whole set, so every insert is new
+        let keys_to_insert: Vec<u64> = (0..1024u64)
+            .map(|i| (i.wrapping_mul(0x9E37_79B9_7F4A_7C15) % (n * 2)) | 1)
+            .collect();
+        group.throughput(criterion::Throughput::Elements(keys_to_insert.len() as u64));
+
+        let btree_set: BTreeSet<u64> = BTreeSet::from_iter(keys.iter().cloned());
+        let binary_set: BinarySet<u64> = BinarySet::from_iter(keys.iter().cloned());
+        let chunked_set: ChunkedBinarySet<u64> = ChunkedBinarySet::from(binary_set.clone());
+
+        group.bench_function(BenchmarkId::new("BTreeSet", n), |b| {
+            b.iter_batched_ref(
+                || btree_set.clone(),
+                |set| bench_set_insert(set, &keys_to_insert),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("BinarySet", n), |b| {
+            b.iter_batched_ref(
+                || binary_set.clone(),
+                |set| bench_set_insert(set, &keys_to_insert),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("ChunkedBinarySet", n), |b| {
+            b.iter_batched_ref(
+                || chunked_set.clone(),
+                |set| bench_set_insert(set, &keys_to_insert),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_set_insert<TSet: SetMut<u64>>(set: &mut TSet, keys: &[u64]) -> usize {
+    keys.iter().filter(|key| set.insert(**key)).count()
+}
@@ -0,0 +1,217 @@
+//! Lazy set operations over two sorted, deduplicated slices, shared by the set types.
+
+use core::cmp::Ordering;
+use core::iter::FusedIterator;
+
+use crate::compare::{Compare, Natural};
+
+/// Lazy iterator over the elements in either of two sorted slices, returned by `union`.
+#[derive(Debug)]
+pub struct Union<'a, T, C = Natural> {
+    pub(crate) a: &'a [T],
+    pub(crate) b: &'a [T],
+    pub(crate) compare: &'a C,
+}
+
+/// Lazy iterator over the elements in both of two sorted slices, returned by `intersection`.
+#[derive(Debug)]
+pub struct Intersection<'a, T, C = Natural> {
+    pub(crate) a: &'a [T],
+    pub(crate) b: &'a [T],
+    pub(crate) compare: &'a C,
+}
+
+/// Lazy iterator over the elements of one sorted slice missing from another, returned by `difference`.
+#[derive(Debug)]
+pub struct Difference<'a, T, C = Natural> {
+    pub(crate) a: &'a [T],
+    pub(crate) b: &'a [T],
+    pub(crate) compare: &'a C,
+}
+
+/// Lazy iterator over the elements in exactly one of two sorted slices, returned by `symmetric_difference`.
+#[derive(Debug)]
+pub struct SymmetricDifference<'a, T, C = Natural> {
+    pub(crate) a: &'a [T],
+    pub(crate) b: &'a [T],
+    pub(crate) compare: &'a C,
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Union<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.a.split_first(), self.b.split_first()) {
+            (Some((x, a)), Some((y, b))) => match self.compare.compare(x, y) {
+                Ordering::Less => {
+                    self.a = a;
+                    Some(x)
+                }
+                Ordering::Greater => {
+                    self.b = b;
+                    Some(y)
+                }
+                Ordering::Equal => {
+                    self.a = a;
+                    self.b = b;
+                    Some(x)
+                }
+            },
+            (Some((x, a)), None) => {
+                self.a = a;
+                Some(x)
+            }
+            (None, Some((y, b))) => {
+                self.b = b;
+                Some(y)
+            }
+            (None, None) => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (
+            self.a.len().max(self.b.len()),
+            Some(self.a.len() + self.b.len()),
+        )
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Intersection<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (x, a) = self.a.split_first()?;
+            let (y, b) = self.b.split_first()?;
+            match self.compare.compare(x, y) {
+                Ordering::Less => self.a = a,
+                Ordering::Greater => self.b = b,
+                Ordering::Equal => {
+                    self.a = a;
+                    self.b = b;
+                    return Some(x);
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.a.len().min(self.b.len())))
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Difference<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (x, a) = self.a.split_first()?;
+            let Some((y, b)) = self.b.split_first() else {
+                self.a = a;
+                return Some(x);
+            };
+            match self.compare.compare(x, y) {
+                Ordering::Less => {
+                    self.a = a;
+                    return Some(x);
+                }
+                Ordering::Greater => self.b = b,
+                Ordering::Equal => {
+                    self.a = a;
+                    self.b = b;
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (
+            self.a.len().saturating_sub(self.b.len()),
+            Some(self.a.len()),
+        )
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for SymmetricDifference<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.a.split_first(), self.b.split_first()) {
+                (Some((x, a)), Some((y, b))) => match self.compare.compare(x, y) {
+                    Ordering::Less => {
+                        self.a = a;
+                        return Some(x);
+                    }
+                    Ordering::Greater => {
+                        self.b = b;
+                        return Some(y);
+                    }
+                    Ordering::Equal => {
+                        self.a = a;
+                        self.b = b;
+                    }
+                },
+                (Some((x, a)), None) => {
+                    self.a = a;
+                    return Some(x);
+                }
+                (None, Some((y, b))) => {
+                    self.b = b;
+                    return Some(y);
+                }
+                (None, None) => return None,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.a.len() + self.b.len()))
+    }
+}
+
+impl<T, C: Compare<T>> FusedIterator for Union<'_, T, C> {}
+impl<T, C: Compare<T>> FusedIterator for Intersection<'_, T, C> {}
+impl<T, C: Compare<T>> FusedIterator for Difference<'_, T, C> {}
+impl<T, C: Compare<T>> FusedIterator for SymmetricDifference<'_, T, C> {}
+
+impl<T, C> Clone for Union<'_, T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a,
+            b: self.b,
+            compare: self.compare,
+        }
+    }
+}
+
+impl<T, C> Clone for Intersection<'_, T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a,
+            b: self.b,
+            compare: self.compare,
+        }
+    }
+}
+
+impl<T, C> Clone for Difference<'_, T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a,
+            b: self.b,
+            compare: self.compare,
+        }
+    }
+}
+
+impl<T, C> Clone for SymmetricDifference<'_, T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a,
+            b: self.b,
+            compare: self.compare,
+        }
+    }
+}
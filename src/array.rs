@@ -0,0 +1,29 @@
+//! Sets and maps with a fixed capacity of `N`, stored inline so they never allocate.
+//!
+//! The elements are kept sorted in an [`ArrayBuffer`], and are searched and combined with the
+//! same code as [`BinarySet`](crate::set::BinarySet). Only `core` is used, so they are
+//! available in `#![no_std]` builds without `alloc` by turning off the default `std` feature.
+//! Adding to a full collection hands back what was not added in a [`CapacityError`].
+//!
+//! ```
+//! use binary_tree_collections::array::ArrayBinarySet;
+//! use binary_tree_collections::error::CapacityError;
+//! use cc_traits::{Get, Insert};
+//!
+//! let mut set: ArrayBinarySet<u32, 3> = ArrayBinarySet::new();
+//! assert_eq!(set.insert(2), Ok(true));
+//! assert_eq!(set.insert(1), Ok(true));
+//! assert_eq!(set.insert(2), Ok(false));
+//! assert_eq!(set.insert(3), Ok(true));
+//! assert_eq!(set.insert(4), Err(CapacityError(4)));
+//! assert!(set.contains(&1));
+//! assert_eq!(set.range(2..).collect::<Vec<_>>(), vec![&2, &3]);
+//! ```
+
+pub mod map;
+pub mod set;
+
+pub use crate::algebra::{Difference, Intersection, SymmetricDifference, Union};
+pub use crate::buffer::{ArrayBuffer, IntoIter};
+pub use map::ArrayBinaryMap;
+pub use set::ArrayBinarySet;
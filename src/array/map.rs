@@ -0,0 +1,596 @@
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::{FusedIterator, Zip};
+use core::mem;
+use core::ops::RangeBounds;
+use core::slice;
+
+use cc_traits::{
+    covariant_item_mut, covariant_item_ref, covariant_key_ref, simple_collection_mut,
+    simple_collection_ref, simple_keyed_ref, Clear, Collection, CollectionMut, CollectionRef, Get,
+    GetKeyValue, GetKeyValueMut, GetMut, Keyed, KeyedRef, Len, MapInsert, MapIter, MapIterMut,
+    Remove, SimpleCollectionMut, SimpleCollectionRef, SimpleKeyedRef,
+};
+
+use crate::array::{self, ArrayBuffer};
+use crate::buffer::Retain;
+#[cfg(feature = "std")]
+use crate::compare::Natural;
+use crate::error::CapacityError;
+#[cfg(feature = "std")]
+use crate::map::BinaryMap;
+use crate::search;
+
+/// A sorted map of at most `N` entries, stored inline as parallel arrays of keys and values.
+pub struct ArrayBinaryMap<K, V, const N: usize> {
+    keys: ArrayBuffer<K, N>,
+    values: ArrayBuffer<V, N>,
+}
+
+impl<K, V, const N: usize> ArrayBinaryMap<K, V, N> {
+    /// Makes a new, empty map.
+    pub const fn new() -> Self {
+        Self {
+            keys: ArrayBuffer::new(),
+            values: ArrayBuffer::new(),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.keys.is_full()
+    }
+
+    /// The keys, in order.
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// The values, in the order of their keys.
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+
+    /// Iterates over the entries in order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.keys.iter().zip(self.values.iter()),
+        }
+    }
+
+    /// Iterates over the entries in order, with mutable values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.keys.iter().zip(self.values.iter_mut()),
+        }
+    }
+
+    /// Iterates in order over the entries with keys within `range`.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(&range, Q::cmp);
+        let indices = search::range(&self.keys, &range, |q: &Q, k: &K| q.cmp(k.borrow()));
+        Iter {
+            inner: self.keys[indices.clone()]
+                .iter()
+                .zip(self.values[indices].iter()),
+        }
+    }
+
+    /// The entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        Some((self.keys.get(index)?, &self.values[index]))
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        Some((self.keys.get(index)?, &mut self.values[index]))
+    }
+
+    /// Removes the entry at position `index`, shifting the ones after it down.
+    pub fn remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.keys.len() {
+            return None;
+        }
+        Some((self.keys.remove(index), self.values.remove(index)))
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.get_index(self.keys.len().checked_sub(1)?)
+    }
+
+    /// Removes the entry with the smallest key. This shifts every other entry down.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.remove_index(0)
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        Some((self.keys.pop()?, self.values.pop()?))
+    }
+
+    /// Keeps only the entries for which `keep` returns true, in one pass.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        // truncating sets the length before dropping, so the two guards end at the same length
+        let mut keys = Retain::new(&mut self.keys, ArrayBuffer::truncate);
+        let mut values = Retain::new(&mut self.values, ArrayBuffer::truncate);
+        while let (Some(key), Some(value)) =
+            (keys.unread().first(), values.unread_mut().first_mut())
+        {
+            if keep(key, value) {
+                keys.keep(1);
+                values.keep(1);
+            } else {
+                keys.skip(1);
+                values.skip(1);
+            }
+        }
+    }
+
+    /// Finds the index of the entry with a key equal to `key`.
+    fn find<Q: ?Sized + Ord>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        search::find(&self.keys, key, |q, k: &K| q.cmp(k.borrow()))
+    }
+}
+
+impl<K: Ord, V, const N: usize> ArrayBinaryMap<K, V, N> {
+    /// Collects `iter` into a map, keeping the last value for each key, or hands back the first
+    /// entry with a new key that did not fit.
+    pub fn try_from_iter<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+    ) -> Result<Self, CapacityError<(K, V)>> {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.insert(key, value)?;
+        }
+        Ok(map)
+    }
+}
+
+impl<K, V, const N: usize> Default for ArrayBinaryMap<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, V: Clone, const N: usize> Clone for ArrayBinaryMap<K, V, N> {
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys.clone(),
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize> fmt::Debug for ArrayBinaryMap<K, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, const N: usize> PartialEq for ArrayBinaryMap<K, V, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.keys() == other.keys() && self.values() == other.values()
+    }
+}
+
+impl<K: Eq, V: Eq, const N: usize> Eq for ArrayBinaryMap<K, V, N> {}
+
+#[cfg(feature = "std")]
+impl<K, V, const N: usize> From<ArrayBinaryMap<K, V, N>> for BinaryMap<K, V> {
+    fn from(map: ArrayBinaryMap<K, V, N>) -> Self {
+        let (keys, values) = map.into_iter().unzip();
+        BinaryMap::from_sorted_parts_unchecked(keys, values, Natural)
+    }
+}
+
+impl<K, V, const N: usize> IntoIterator for ArrayBinaryMap<K, V, N> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.keys.into_iter().zip(self.values),
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a ArrayBinaryMap<K, V, N> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a mut ArrayBinaryMap<K, V, N> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const N: usize> Collection for ArrayBinaryMap<K, V, N> {
+    type Item = V;
+}
+
+impl<K, V, const N: usize> CollectionRef for ArrayBinaryMap<K, V, N> {
+    type ItemRef<'a>
+        = &'a V
+    where
+        Self: 'a;
+
+    covariant_item_ref!();
+}
+
+impl<K, V, const N: usize> CollectionMut for ArrayBinaryMap<K, V, N> {
+    type ItemMut<'a>
+        = &'a mut V
+    where
+        Self: 'a;
+
+    covariant_item_mut!();
+}
+
+impl<K, V, const N: usize> SimpleCollectionRef for ArrayBinaryMap<K, V, N> {
+    simple_collection_ref!();
+}
+
+impl<K, V, const N: usize> SimpleCollectionMut for ArrayBinaryMap<K, V, N> {
+    simple_collection_mut!();
+}
+
+impl<K, V, const N: usize> Keyed for ArrayBinaryMap<K, V, N> {
+    type Key = K;
+}
+
+impl<K, V, const N: usize> KeyedRef for ArrayBinaryMap<K, V, N> {
+    type KeyRef<'a>
+        = &'a K
+    where
+        Self: 'a;
+
+    covariant_key_ref!();
+}
+
+impl<K, V, const N: usize> SimpleKeyedRef for ArrayBinaryMap<K, V, N> {
+    simple_keyed_ref!();
+}
+
+impl<K, V, const N: usize> Len for ArrayBinaryMap<K, V, N> {
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const N: usize> Get<&'a Q> for ArrayBinaryMap<K, V, N> {
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let index = self.find(key).ok()?;
+        Some(&self.values[index])
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const N: usize> GetMut<&'a Q>
+    for ArrayBinaryMap<K, V, N>
+{
+    fn get_mut(&mut self, key: &'a Q) -> Option<Self::ItemMut<'_>> {
+        let index = self.find(key).ok()?;
+        Some(&mut self.values[index])
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const N: usize> GetKeyValue<&'a Q>
+    for ArrayBinaryMap<K, V, N>
+{
+    fn get_key_value(&self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemRef<'_>)> {
+        let index = self.find(key).ok()?;
+        Some((&self.keys[index], &self.values[index]))
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const N: usize> GetKeyValueMut<&'a Q>
+    for ArrayBinaryMap<K, V, N>
+{
+    fn get_key_value_mut(&mut self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemMut<'_>)> {
+        let index = self.find(key).ok()?;
+        Some((&self.keys[index], &mut self.values[index]))
+    }
+}
+
+impl<K: Ord, V, const N: usize> MapInsert<K> for ArrayBinaryMap<K, V, N> {
+    /// The value replaced, or the entry if its key is new and the map is full.
+    /// Replacing the value of a key already present succeeds even when the map is full.
+    type Output = Result<Option<V>, CapacityError<(K, V)>>;
+
+    fn insert(&mut self, key: K, value: Self::Item) -> Self::Output {
+        match self.find(&key) {
+            Ok(index) => Ok(Some(mem::replace(&mut self.values[index], value))),
+            Err(_) if self.is_full() => Err(CapacityError((key, value))),
+            Err(index) => {
+                let keys = self.keys.try_insert(index, key);
+                if keys.is_err() || self.values.try_insert(index, value).is_err() {
+                    unreachable!("the keys and values have room, as the map is not full")
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const N: usize> Remove<&'a Q>
+    for ArrayBinaryMap<K, V, N>
+{
+    fn remove(&mut self, key: &'a Q) -> Option<Self::Item> {
+        let index = self.find(key).ok()?;
+        // both are removed before either is dropped, so a panicking drop leaves them in step
+        let (key, value) = (self.keys.remove(index), self.values.remove(index));
+        drop(key);
+        Some(value)
+    }
+}
+
+impl<K, V, const N: usize> Clear for ArrayBinaryMap<K, V, N> {
+    /// Takes the keys and values before dropping either, so a panicking drop leaves both empty.
+    fn clear(&mut self) {
+        let keys = mem::take(&mut self.keys);
+        let values = mem::take(&mut self.values);
+        drop((keys, values));
+    }
+}
+
+impl<K, V, const N: usize> cc_traits::Iter for ArrayBinaryMap<K, V, N> {
+    type Iter<'a>
+        = slice::Iter<'a, V>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.values.iter()
+    }
+}
+
+impl<K, V, const N: usize> MapIter for ArrayBinaryMap<K, V, N> {
+    type Iter<'a>
+        = Iter<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+impl<K, V, const N: usize> MapIterMut for ArrayBinaryMap<K, V, N> {
+    type IterMut<'a>
+        = IterMut<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.iter_mut()
+    }
+}
+
+/// Iterator over the entries of an [`ArrayBinaryMap`], returned by `iter` and `range`.
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    inner: Zip<slice::Iter<'a, K>, slice::Iter<'a, V>>,
+}
+
+/// Mutable iterator over the entries of an [`ArrayBinaryMap`], returned by `iter_mut`.
+#[derive(Debug)]
+pub struct IterMut<'a, K, V> {
+    inner: Zip<slice::Iter<'a, K>, slice::IterMut<'a, V>>,
+}
+
+/// Owning iterator over the entries of an [`ArrayBinaryMap`], returned by `into_iter`.
+#[derive(Debug)]
+pub struct IntoIter<K, V, const N: usize> {
+    inner: Zip<array::IntoIter<K, N>, array::IntoIter<V, N>>,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K, V, const N: usize> Iterator for IntoIter<K, V, N> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, const N: usize> DoubleEndedIterator for IntoIter<K, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V, const N: usize> ExactSizeIterator for IntoIter<K, V, N> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+impl<K, V, const N: usize> FusedIterator for IntoIter<K, V, N> {}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::array::map::*;
+    use crate::test_support::{assert_lockstep_after, Brittle};
+    use std::collections::BTreeMap;
+
+    #[test]
+    pub fn insert_remove_matches_btree_map() {
+        let mut map = ArrayBinaryMap::<u32, u32, 16>::new();
+        let mut btree = BTreeMap::new();
+        for (i, x) in (0..200).map(|x: u32| x.wrapping_mul(37) % 101).enumerate() {
+            match map.insert(x, i as u32) {
+                Ok(replaced) => assert_eq!(replaced, btree.insert(x, i as u32)),
+                Err(CapacityError(rejected)) => {
+                    assert_eq!(rejected, (x, i as u32));
+                    assert!(map.is_full() && !btree.contains_key(&x));
+                }
+            }
+            assert!(map.iter().eq(btree.iter()));
+            if x % 3 == 0 {
+                assert_eq!(map.remove(&x), btree.remove(&x));
+            }
+        }
+        assert_eq!(map.len(), btree.len());
+        for x in 0..110 {
+            assert_eq!(map.get_key_value(&x), btree.get_key_value(&x));
+        }
+    }
+
+    #[test]
+    pub fn full_map_replaces_values() {
+        let mut map =
+            ArrayBinaryMap::<_, _, 2>::try_from_iter([("b", 1), ("a", 2), ("b", 3)]).unwrap();
+        assert!(map.is_full());
+        assert_eq!(map.insert("a", 4), Ok(Some(2)));
+        assert_eq!(map.insert("c", 5), Err(CapacityError(("c", 5))));
+        assert_eq!(format!("{map:?}"), r#"{"a": 4, "b": 3}"#);
+
+        for (_, v) in &mut map {
+            *v *= 10;
+        }
+        assert_eq!(map.range("b"..).collect::<Vec<_>>(), vec![(&"b", &30)]);
+        assert_eq!(map.clone().into_iter().next_back(), Some(("b", 30)));
+        assert_eq!(map.pop_last(), Some(("b", 30)));
+        assert_eq!(map.first(), Some((&"a", &40)));
+    }
+
+    #[test]
+    pub fn panicking_key_drop_keeps_lockstep() {
+        let entries = (0..5).map(|k| (Brittle(k, k == 2), k));
+        let mut map = ArrayBinaryMap::<_, _, 8>::try_from_iter(entries).unwrap();
+        assert_lockstep_after(
+            &mut map,
+            |map| {
+                map.remove(&2);
+            },
+            |k| k.0,
+            "dropped a brittle key",
+        );
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get(&3), Some(&3));
+
+        let entries = (0..5).map(|k| (Brittle(k, k == 1), k));
+        let mut map = ArrayBinaryMap::<_, _, 8>::try_from_iter(entries).unwrap();
+        assert_lockstep_after(
+            &mut map,
+            |map| map.clear(),
+            |k| k.0,
+            "dropped a brittle key",
+        );
+        assert!(map.is_empty());
+
+        let entries = (0..5).map(|k| (Brittle(k, k == 3), k));
+        let mut map = ArrayBinaryMap::<_, _, 8>::try_from_iter(entries).unwrap();
+        assert_lockstep_after(
+            &mut map,
+            |map| map.retain(|k, _| k.0 % 2 == 0),
+            |k| k.0,
+            "dropped a brittle key",
+        );
+        assert_eq!(
+            map.keys().iter().map(|k| k.0).collect::<Vec<_>>(),
+            vec![0, 2, 4]
+        );
+    }
+
+    #[test]
+    pub fn ends_and_positions() {
+        let mut map =
+            ArrayBinaryMap::<_, _, 8>::try_from_iter((1..=6).map(|k| (k, k * 10))).unwrap();
+        assert_eq!(map.first(), Some((&1, &10)));
+        assert_eq!(map.last(), Some((&6, &60)));
+        assert_eq!(map.get_index(2), Some((&3, &30)));
+        if let Some((_, v)) = map.get_index_mut(2) {
+            *v += 1;
+        }
+        assert_eq!(map.remove_index(2), Some((3, 31)));
+        assert_eq!(map.remove_index(5), None);
+        assert_eq!(map.pop_first(), Some((1, 10)));
+        assert_eq!(map.pop_last(), Some((6, 60)));
+        map.retain(|k, v| {
+            *v += 1;
+            *k != 4
+        });
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&2, &21), (&5, &51)]);
+
+        map.clear();
+        assert_eq!(map.first(), None);
+        assert_eq!(map.pop_first(), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    pub fn into_binary_map() {
+        let map = ArrayBinaryMap::<_, _, 4>::try_from_iter([(2, 'b'), (1, 'a')]).unwrap();
+        assert_eq!(
+            BinaryMap::from(map),
+            BinaryMap::from_iter([(1, 'a'), (2, 'b')])
+        );
+    }
+}
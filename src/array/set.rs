@@ -0,0 +1,339 @@
+use core::borrow::Borrow;
+use core::fmt;
+use core::ops::RangeBounds;
+use core::slice;
+
+use cc_traits::{
+    covariant_item_ref, simple_collection_ref, Clear, Collection, CollectionRef, Get, Insert, Len,
+    Remove, SimpleCollectionRef,
+};
+
+use crate::algebra::{Difference, Intersection, SymmetricDifference, Union};
+use crate::array::{ArrayBuffer, IntoIter};
+use crate::compare::Natural;
+use crate::error::CapacityError;
+use crate::search;
+#[cfg(feature = "std")]
+use crate::set::BinarySet;
+
+/// A sorted set of at most `N` elements, stored inline.
+pub struct ArrayBinarySet<T, const N: usize>(ArrayBuffer<T, N>);
+
+impl<T, const N: usize> ArrayBinarySet<T, N> {
+    /// Makes a new, empty set.
+    pub const fn new() -> Self {
+        Self(ArrayBuffer::new())
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.0.is_full()
+    }
+
+    /// The elements, in order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Iterates over the elements in order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Iterates in order over the elements within `range`.
+    pub fn range<Q, R>(&self, range: R) -> slice::Iter<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(&range, Q::cmp);
+        let indices = search::range(&self.0, &range, |q: &Q, x: &T| q.cmp(x.borrow()));
+        self.0[indices].iter()
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.0.first()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.0.last()
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        (!self.0.is_empty()).then(|| self.0.remove(0))
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    /// Keeps only the elements for which `keep` returns true.
+    pub fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        self.0.retain(keep)
+    }
+}
+
+impl<T: Ord, const N: usize> ArrayBinarySet<T, N> {
+    /// Collects `iter` into a set, or hands back the first element that did not fit.
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(iter: I) -> Result<Self, CapacityError<T>> {
+        let mut set = Self::new();
+        for element in iter {
+            set.insert(element)?;
+        }
+        Ok(set)
+    }
+
+    /// Visits the elements in `self` or `other`, in ascending order.
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T> {
+        Union {
+            a: &self.0,
+            b: &other.0,
+            compare: &Natural,
+        }
+    }
+
+    /// Visits the elements in both `self` and `other`, in ascending order.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T> {
+        Intersection {
+            a: &self.0,
+            b: &other.0,
+            compare: &Natural,
+        }
+    }
+
+    /// Visits the elements in `self` but not in `other`, in ascending order.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T> {
+        Difference {
+            a: &self.0,
+            b: &other.0,
+            compare: &Natural,
+        }
+    }
+
+    /// Visits the elements in exactly one of `self` and `other`, in ascending order.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T> {
+        SymmetricDifference {
+            a: &self.0,
+            b: &other.0,
+            compare: &Natural,
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+impl<T, const N: usize> Default for ArrayBinarySet<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayBinarySet<T, N> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayBinarySet<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for ArrayBinarySet<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArrayBinarySet<T, N> {}
+
+#[cfg(feature = "std")]
+impl<T, const N: usize> From<ArrayBinarySet<T, N>> for BinarySet<T> {
+    fn from(set: ArrayBinarySet<T, N>) -> Self {
+        BinarySet::from_sorted_vec_unchecked(set.into_iter().collect(), Natural)
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayBinarySet<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayBinarySet<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, const N: usize> Collection for ArrayBinarySet<T, N> {
+    type Item = T;
+}
+
+impl<T, const N: usize> CollectionRef for ArrayBinarySet<T, N> {
+    type ItemRef<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    covariant_item_ref!();
+}
+
+impl<T, const N: usize> SimpleCollectionRef for ArrayBinarySet<T, N> {
+    simple_collection_ref!();
+}
+
+impl<T, const N: usize> Len for ArrayBinarySet<T, N> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, T: Borrow<Q>, const N: usize> Get<&'a Q> for ArrayBinarySet<T, N> {
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let index = search::find(&self.0, key, |q, x: &T| q.cmp(x.borrow())).ok()?;
+        Some(&self.0[index])
+    }
+}
+
+impl<T: Ord, const N: usize> Insert for ArrayBinarySet<T, N> {
+    /// Whether the element was new, or the element if it was new and the set is full.
+    type Output = Result<bool, CapacityError<T>>;
+
+    fn insert(&mut self, element: Self::Item) -> Self::Output {
+        match search::find(&self.0, &element, Ord::cmp) {
+            Ok(_) => Ok(false),
+            Err(index) => self.0.try_insert(index, element).map(|()| true),
+        }
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, T: Borrow<Q>, const N: usize> Remove<&'a Q> for ArrayBinarySet<T, N> {
+    fn remove(&mut self, key: &'a Q) -> Option<Self::Item> {
+        let index = search::find(&self.0, key, |q, x: &T| q.cmp(x.borrow())).ok()?;
+        Some(self.0.remove(index))
+    }
+}
+
+impl<T, const N: usize> Clear for ArrayBinarySet<T, N> {
+    fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+impl<T, const N: usize> cc_traits::Iter for ArrayBinarySet<T, N> {
+    type Iter<'a>
+        = slice::Iter<'a, T>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::array::set::*;
+    use std::collections::BTreeSet;
+    use std::ops::Bound;
+
+    #[test]
+    pub fn insert_remove_matches_btree_set() {
+        let mut set = ArrayBinarySet::<u32, 16>::new();
+        let mut btree = BTreeSet::new();
+        for x in (0..200).map(|x: u32| x.wrapping_mul(37) % 101) {
+            match set.insert(x) {
+                Ok(inserted) => assert_eq!(inserted, btree.insert(x)),
+                Err(CapacityError(rejected)) => {
+                    assert_eq!(rejected, x);
+                    assert!(set.is_full() && !btree.contains(&x));
+                }
+            }
+            assert!(set.iter().eq(btree.iter()));
+            if x % 3 == 0 {
+                assert_eq!(set.remove(&x), btree.take(&x));
+            }
+        }
+        assert_eq!(set.len(), btree.len());
+        for x in 0..110 {
+            assert_eq!(set.get(&x), btree.get(&x));
+        }
+    }
+
+    #[test]
+    pub fn range_matches_btree_set() {
+        let values = [1, 3, 4, 7, 8, 9, 12, 15, 20, 21];
+        let set = ArrayBinarySet::<i32, 10>::try_from_iter(values).unwrap();
+        let btree = BTreeSet::from_iter(values);
+
+        for a in 0..23 {
+            for b in a..23 {
+                for bounds in [
+                    (Bound::Included(a), Bound::Included(b)),
+                    (Bound::Included(a), Bound::Excluded(b)),
+                    (Bound::Excluded(a), Bound::Included(b)),
+                    (Bound::Unbounded, Bound::Included(b)),
+                    (Bound::Included(a), Bound::Unbounded),
+                ] {
+                    let expected: Vec<_> = btree.range(bounds).collect();
+                    assert_eq!(set.range(bounds).collect::<Vec<_>>(), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn set_algebra_matches_btree_set() {
+        let a = ArrayBinarySet::<u32, 8>::try_from_iter([1, 3, 5, 7, 9]).unwrap();
+        let b = ArrayBinarySet::<u32, 8>::try_from_iter([3, 4, 5, 6]).unwrap();
+        let (x, y) = (BTreeSet::from_iter(&a), BTreeSet::from_iter(&b));
+
+        assert!(a.union(&b).eq(x.union(&y).copied()));
+        assert!(a.intersection(&b).eq(x.intersection(&y).copied()));
+        assert!(a.difference(&b).eq(x.difference(&y).copied()));
+        assert!(a
+            .symmetric_difference(&b)
+            .eq(x.symmetric_difference(&y).copied()));
+        assert!(!a.is_disjoint(&b));
+        assert!(a.is_superset(&ArrayBinarySet::try_from_iter([3, 9]).unwrap()));
+    }
+
+    #[test]
+    pub fn try_from_iter_rejects_overflow() {
+        let set = ArrayBinarySet::<_, 2>::try_from_iter(["b", "a", "b"]).unwrap();
+        assert_eq!(format!("{set:?}"), r#"{"a", "b"}"#);
+        assert_eq!(
+            ArrayBinarySet::<_, 2>::try_from_iter(["b", "a", "c"]),
+            Err(CapacityError("c"))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    pub fn into_binary_set() {
+        let set = ArrayBinarySet::<_, 4>::try_from_iter(["b", "a"]).unwrap();
+        assert_eq!(BinarySet::from(set), BinarySet::from_iter(["a", "b"]));
+    }
+}
@@ -0,0 +1,391 @@
+//! The inline buffer and the compaction guard that the collections and storages build on.
+//!
+//! Only `core` is used, so [`ArrayBuffer`] backs the fixed-capacity collections in `no_std`
+//! builds as well as the inline part of a small store.
+
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::{fmt, ptr, slice};
+
+use crate::error::CapacityError;
+
+/// Up to `N` elements stored inline, of which the first `len` are initialized.
+pub struct ArrayBuffer<T, const N: usize> {
+    len: usize,
+    items: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> ArrayBuffer<T, N> {
+    /// Makes a new, empty buffer.
+    pub const fn new() -> Self {
+        Self {
+            len: 0,
+            items: [const { MaybeUninit::uninit() }; N],
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    fn as_ptr(&self) -> *const T {
+        self.items.as_ptr().cast()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.items.as_mut_ptr().cast()
+    }
+
+    /// Inserts `element` at `index`, shifting the elements after it up, or hands it back if the
+    /// buffer is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn try_insert(&mut self, index: usize, element: T) -> Result<(), CapacityError<T>> {
+        assert!(index <= self.len, "insertion index is out of bounds");
+        if self.is_full() {
+            return Err(CapacityError(element));
+        }
+        // SAFETY: there is room for one more, and the elements from `index` move up by one.
+        unsafe {
+            let at = self.as_mut_ptr().add(index);
+            ptr::copy(at, at.add(1), self.len - index);
+            ptr::write(at, element);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Appends `element`, or hands it back if the buffer is full.
+    pub fn try_push(&mut self, element: T) -> Result<(), CapacityError<T>> {
+        self.try_insert(self.len, element)
+    }
+
+    /// Removes the element at `index`, shifting the elements after it down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "removal index is out of bounds");
+        self.len -= 1;
+        // SAFETY: `index` was initialized, and the elements after it move down over it.
+        unsafe {
+            let at = self.as_mut_ptr().add(index);
+            let element = ptr::read(at);
+            ptr::copy(at.add(1), at, self.len - index);
+            element
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        // SAFETY: the last element was initialized, and is no longer counted.
+        Some(unsafe { ptr::read(self.as_ptr().add(self.len)) })
+    }
+
+    /// Drops the elements from `len` onwards.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let old_len = mem::replace(&mut self.len, len);
+        // SAFETY: the items from `len` were initialized, and are no longer counted.
+        unsafe {
+            let tail = self.as_mut_ptr().add(len);
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(tail, old_len - len));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Moves the elements from `at` onwards into a new buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the length.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "split index is out of bounds");
+        let mut tail = Self::new();
+        tail.len = self.len - at;
+        // SAFETY: the items from `at` are initialized, and move to the front of `tail`.
+        unsafe { ptr::copy_nonoverlapping(self.as_ptr().add(at), tail.as_mut_ptr(), tail.len) };
+        self.len = at;
+        tail
+    }
+
+    /// Keeps only the elements for which `keep` returns true, in order.
+    pub fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        Retain::new(self, Self::truncate).run(keep)
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T, const N: usize> Default for ArrayBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for ArrayBuffer<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: the first `len` items are initialized.
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayBuffer<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` items are initialized.
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayBuffer<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayBuffer<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for ArrayBuffer<T, N> {
+    /// # Panics
+    ///
+    /// Panics if there are more than `N` elements.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut buffer = Self::new();
+        buffer.extend(iter);
+        buffer
+    }
+}
+
+impl<T, const N: usize> Extend<T> for ArrayBuffer<T, N> {
+    /// # Panics
+    ///
+    /// Panics if the elements do not fit.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for element in iter {
+            if self.try_push(element).is_err() {
+                panic!("buffer capacity of {N} exceeded");
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayBuffer<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let end = mem::replace(&mut self.len, 0);
+        // SAFETY: `self` no longer counts its items, so they are only dropped by the iterator.
+        let items = unsafe { ptr::read(&self.items) };
+        IntoIter {
+            items,
+            start: 0,
+            end,
+        }
+    }
+}
+
+/// Owning iterator over an [`ArrayBuffer`].
+pub struct IntoIter<T, const N: usize> {
+    /// The items from `start` to `end` are initialized and not yet yielded.
+    items: [MaybeUninit<T>; N],
+    start: usize,
+    end: usize,
+}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        let remaining = &mut self.items[self.start..self.end];
+        self.start = self.end;
+        // SAFETY: the remaining items are initialized, and no longer counted.
+        unsafe { ptr::drop_in_place(remaining as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        // SAFETY: the item was initialized, and is no longer counted.
+        Some(unsafe { self.items[self.start - 1].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.end - self.start, Some(self.end - self.start))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        // SAFETY: the item was initialized, and is no longer counted.
+        Some(unsafe { self.items[self.end].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for IntoIter<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIter")
+            .field("remaining", &self.len())
+            .finish()
+    }
+}
+
+/// Compacts a buffer front to back. Kept elements are swapped down over rejected ones, which
+/// are rotated to the end and truncated when the guard drops, so a panic leaves the order intact.
+pub(crate) struct Retain<'a, T, B: DerefMut<Target = [T]>> {
+    buffer: &'a mut B,
+    truncate: fn(&mut B, usize),
+    kept: usize,
+    read: usize,
+    element: PhantomData<T>,
+}
+
+impl<'a, T, B: DerefMut<Target = [T]>> Retain<'a, T, B> {
+    pub(crate) fn new(buffer: &'a mut B, truncate: fn(&mut B, usize)) -> Self {
+        Self {
+            buffer,
+            truncate,
+            kept: 0,
+            read: 0,
+            element: PhantomData,
+        }
+    }
+
+    pub(crate) fn run(mut self, mut keep: impl FnMut(&T) -> bool) {
+        while let Some(element) = self.unread().first() {
+            if keep(element) {
+                self.keep(1);
+            } else {
+                self.skip(1);
+            }
+        }
+    }
+
+    pub(crate) fn unread(&self) -> &[T] {
+        &self.buffer[self.read..]
+    }
+
+    pub(crate) fn unread_mut(&mut self) -> &mut [T] {
+        &mut self.buffer[self.read..]
+    }
+
+    /// Keeps the next `count` unread elements.
+    pub(crate) fn keep(&mut self, count: usize) {
+        if self.kept != self.read {
+            for offset in 0..count {
+                self.buffer.swap(self.kept + offset, self.read + offset);
+            }
+        }
+        self.kept += count;
+        self.read += count;
+    }
+
+    /// Rejects the next `count` unread elements.
+    pub(crate) fn skip(&mut self, count: usize) {
+        self.read += count;
+    }
+}
+
+impl<T, B: DerefMut<Target = [T]>> Drop for Retain<'_, T, B> {
+    fn drop(&mut self) {
+        let rejected = self.read - self.kept;
+        self.buffer[self.kept..].rotate_left(rejected);
+        let len = self.buffer.len() - rejected;
+        (self.truncate)(self.buffer, len);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::buffer::*;
+    use crate::test_support::Counted;
+
+    #[test]
+    pub fn array_buffer_rejects_when_full() {
+        let mut buffer: ArrayBuffer<u32, 3> = ArrayBuffer::new();
+        assert_eq!(buffer.try_push(3), Ok(()));
+        assert_eq!(buffer.try_insert(0, 1), Ok(()));
+        assert_eq!(buffer.try_insert(1, 2), Ok(()));
+        assert!(buffer.is_full());
+        assert_eq!(buffer.try_insert(0, 0), Err(CapacityError(0)));
+        assert_eq!(&*buffer, &[1, 2, 3]);
+
+        assert_eq!(buffer.remove(0), 1);
+        assert_eq!(&*buffer.split_off(1), &[3]);
+        assert_eq!(buffer.pop(), Some(2));
+        assert_eq!(buffer.pop(), None);
+    }
+
+    #[test]
+    pub fn array_buffer_drops_each_element_once() {
+        let drops = Rc::new(Cell::new(0));
+        let counted = |x| Counted(x, drops.clone());
+
+        let mut buffer: ArrayBuffer<Counted, 4> = (0..4).map(counted).collect();
+        drop(buffer.try_push(counted(4)));
+        assert_eq!(drops.get(), 1);
+        drop(buffer.remove(1));
+        buffer.truncate(2);
+        assert_eq!(drops.get(), 1 + 2);
+
+        let mut iter = buffer.clone().into_iter();
+        assert_eq!(iter.next_back().map(|c| c.0), Some(2));
+        drop(iter);
+        assert_eq!(drops.get(), 3 + 2);
+        drop(buffer);
+        assert_eq!(drops.get(), 5 + 2);
+    }
+
+    #[test]
+    pub fn retain_panic_keeps_order() {
+        let mut buffer: ArrayBuffer<u32, 8> = (0..8).collect();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            buffer.retain(|x| {
+                assert!(*x != 5);
+                x % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(&*buffer, &[0, 2, 4, 5, 6, 7]);
+        buffer.retain(|x| x % 2 == 1);
+        assert_eq!(&*buffer, &[5, 7]);
+    }
+}
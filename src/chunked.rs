@@ -0,0 +1,403 @@
+//! Sets and maps stored as a list of bounded sorted chunks, for large mutable collections.
+//!
+//! Each chunk holds at most `CHUNK` elements. A lookup binary searches the chunks by their first
+//! keys and then a single chunk, and an insert moves at most `CHUNK` elements plus, when a full
+//! chunk splits, the list of chunks. With the default `CHUNK` of 1024 this keeps inserts into
+//! millions of elements cheap, where a single sorted `Vec` would move half of them on average.
+//!
+//! ```
+//! use binary_tree_collections::chunked::ChunkedBinarySet;
+//! use cc_traits::{Get, Insert};
+//!
+//! let mut set: ChunkedBinarySet<u32> = ChunkedBinarySet::default();
+//! for x in (0..10_000).rev() {
+//!     set.insert(x);
+//! }
+//! assert!(set.contains(&5_000));
+//! assert_eq!(set.range(10..13).collect::<Vec<_>>(), vec![&10, &11, &12]);
+//! ```
+
+use std::borrow::Borrow;
+use std::iter::{FusedIterator, Map};
+use std::ops::{Bound, RangeBounds};
+use std::{mem, slice};
+
+use crate::search;
+
+pub mod map;
+pub mod set;
+
+pub use map::ChunkedBinaryMap;
+pub use set::ChunkedBinarySet;
+
+/// A position in a list of chunks: the chunk, and the index within it.
+type Position = (usize, usize);
+
+/// Iterators over each of a run of chunks.
+type Chunks<'a, C, I> = Map<slice::Iter<'a, C>, fn(&'a C) -> I>;
+
+/// Mutable iterators over each of a run of chunks.
+type ChunksMut<'a, C, I> = Map<slice::IterMut<'a, C>, fn(&'a mut C) -> I>;
+
+/// The operations used to keep chunks sorted and within their bounds.
+trait Chunk: Default + Sized {
+    type Key;
+    type Item;
+
+    fn keys(&self) -> &[Self::Key];
+
+    fn insert(&mut self, index: usize, item: Self::Item);
+
+    fn remove(&mut self, index: usize) -> Self::Item;
+
+    fn split_off(&mut self, at: usize) -> Self;
+
+    fn append(&mut self, other: &mut Self);
+}
+
+impl<T> Chunk for Vec<T> {
+    type Key = T;
+    type Item = T;
+
+    fn keys(&self) -> &[T] {
+        self
+    }
+
+    fn insert(&mut self, index: usize, item: T) {
+        self.insert(index, item)
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        self.remove(index)
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        self.split_off(at)
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.append(other)
+    }
+}
+
+/// Sorted, non-empty chunks. Chunks are found by binary searching their first keys, and the
+/// number of elements before a chunk is kept in a Fenwick tree of chunk lengths, so that
+/// inserting, removing and counting a run of chunks each take a logarithmic number of steps.
+#[derive(Clone)]
+struct ChunkList<C: Chunk> {
+    chunks: Vec<C>,
+    counts: Vec<usize>,
+    len: usize,
+}
+
+impl<C: Chunk> ChunkList<C> {
+    const fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            counts: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Indexes chunks that are already sorted and non-empty.
+    fn from_chunks(chunks: Vec<C>) -> Self {
+        let mut list = Self {
+            chunks,
+            counts: Vec::new(),
+            len: 0,
+        };
+        list.reindex();
+        list
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The number of elements before `position`.
+    fn offset(&self, (c, i): Position) -> usize {
+        if c >= self.chunks.len() {
+            return self.len;
+        }
+        let (mut node, mut start) = (c, 0);
+        while node > 0 {
+            start += self.counts[node - 1];
+            node &= node - 1;
+        }
+        start + i
+    }
+
+    /// Rebuilds the tree of chunk lengths after chunks were split or merged.
+    fn reindex(&mut self) {
+        self.counts.clear();
+        self.counts
+            .extend(self.chunks.iter().map(|chunk| chunk.keys().len()));
+        self.len = 0;
+        for node in 1..=self.counts.len() {
+            self.len += self.chunks[node - 1].keys().len();
+            let parent = node + (node & node.wrapping_neg());
+            if parent <= self.counts.len() {
+                self.counts[parent - 1] += self.counts[node - 1];
+            }
+        }
+    }
+
+    /// Counts one more or one fewer element in chunk `c`.
+    fn count(&mut self, c: usize, grew: bool) {
+        let mut node = c + 1;
+        while node <= self.counts.len() {
+            if grew {
+                self.counts[node - 1] += 1;
+            } else {
+                self.counts[node - 1] -= 1;
+            }
+            node += node & node.wrapping_neg();
+        }
+        if grew {
+            self.len += 1;
+        } else {
+            self.len -= 1;
+        }
+    }
+
+    /// The position of the first key for which `pred` is false.
+    /// If there is none, this is the chunk after the last.
+    fn locate(&self, mut pred: impl FnMut(&C::Key) -> bool) -> Position {
+        // that key is in the last chunk whose minimum satisfies `pred`, or starts the next one
+        let c = self.chunks.partition_point(|chunk| pred(&chunk.keys()[0]));
+        let Some(keys) = c.checked_sub(1).map(|c| self.chunks[c].keys()) else {
+            return (0, 0);
+        };
+        match keys.partition_point(pred) {
+            i if i == keys.len() => (c, 0),
+            i => (c - 1, i),
+        }
+    }
+
+    /// The position of `key` if it is present, or else where it would be inserted.
+    fn find<Q>(&self, key: &Q) -> Result<Position, Position>
+    where
+        C::Key: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let (c, i) = self.locate(|x| x.borrow() < key);
+        match self.chunks.get(c).and_then(|chunk| chunk.keys().get(i)) {
+            Some(x) if x.borrow() == key => Ok((c, i)),
+            // past the end, the key belongs at the end of the last chunk
+            None if c > 0 => Err((c - 1, self.chunks[c - 1].keys().len())),
+            _ => Err((c, i)),
+        }
+    }
+
+    /// The positions of the first key within `range` and of the first key after it.
+    fn range<Q, R>(&self, range: &R) -> (Position, Position)
+    where
+        C::Key: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(range, Ord::cmp);
+        let start = match range.start_bound() {
+            Bound::Included(s) => self.locate(|x| x.borrow() < s),
+            Bound::Excluded(s) => self.locate(|x| x.borrow() <= s),
+            Bound::Unbounded => (0, 0),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(e) => self.locate(|x| x.borrow() <= e),
+            Bound::Excluded(e) => self.locate(|x| x.borrow() < e),
+            Bound::Unbounded => (self.chunks.len(), 0),
+        };
+        (start, end)
+    }
+
+    /// Inserts `item` at `position`, as returned by `find`, splitting its chunk if it grows
+    /// past `max` elements.
+    fn insert(&mut self, (c, i): Position, item: C::Item, max: usize) {
+        if self.chunks.is_empty() {
+            self.chunks.push(C::default());
+            self.counts.push(0);
+        }
+        self.chunks[c].insert(i, item);
+        self.count(c, true);
+        self.split_if_full(c, max);
+    }
+
+    /// Removes the item at `position`, merging its chunk into a neighbour if it falls below
+    /// half of `max` elements.
+    fn remove(&mut self, (c, i): Position, max: usize) -> C::Item {
+        let item = self.chunks[c].remove(i);
+        self.count(c, false);
+        self.merge_if_small(c, max);
+        item
+    }
+
+    fn clear(&mut self) {
+        // empty the index before dropping anything, in case a drop panics
+        let chunks = mem::take(&mut self.chunks);
+        self.counts.clear();
+        self.len = 0;
+        drop(chunks);
+    }
+
+    /// Splits chunk `c` in half if it holds more than `max` elements.
+    fn split_if_full(&mut self, c: usize, max: usize) {
+        let len = self.chunks[c].keys().len();
+        if len > max {
+            let tail = self.chunks[c].split_off(len / 2);
+            self.chunks.insert(c + 1, tail);
+            self.reindex();
+        }
+    }
+
+    /// Merges chunk `c` into a neighbour if it has fallen below half of `max` elements,
+    /// and drops it if it is the only chunk and empty.
+    fn merge_if_small(&mut self, c: usize, max: usize) {
+        let len = self.chunks[c].keys().len();
+        if len >= max / 2 && len > 0 {
+            return;
+        }
+        if self.chunks.len() == 1 {
+            if len == 0 {
+                self.clear();
+            }
+            return;
+        }
+        let left = if c + 1 < self.chunks.len() { c } else { c - 1 };
+        let mut right = self.chunks.remove(left + 1);
+        self.chunks[left].append(&mut right);
+        self.reindex();
+        self.split_if_full(left, max);
+    }
+}
+
+/// Flattens a run of chunks, the first and last of which may be partial.
+#[derive(Debug, Clone)]
+struct Flat<C, I> {
+    front: I,
+    chunks: C,
+    back: I,
+    len: usize,
+}
+
+impl<C, I> Iterator for Flat<C, I>
+where
+    C: DoubleEndedIterator<Item = I>,
+    I: DoubleEndedIterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(x) = self.front.next() {
+                self.len -= 1;
+                return Some(x);
+            }
+            match self.chunks.next() {
+                Some(chunk) => self.front = chunk,
+                None => break,
+            }
+        }
+        let x = self.back.next()?;
+        self.len -= 1;
+        Some(x)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<C, I> DoubleEndedIterator for Flat<C, I>
+where
+    C: DoubleEndedIterator<Item = I>,
+    I: DoubleEndedIterator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(x) = self.back.next_back() {
+                self.len -= 1;
+                return Some(x);
+            }
+            match self.chunks.next_back() {
+                Some(chunk) => self.back = chunk,
+                None => break,
+            }
+        }
+        let x = self.front.next_back()?;
+        self.len -= 1;
+        Some(x)
+    }
+}
+
+impl<C, I> ExactSizeIterator for Flat<C, I>
+where
+    C: DoubleEndedIterator<Item = I>,
+    I: DoubleEndedIterator,
+{
+}
+
+impl<C, I> FusedIterator for Flat<C, I>
+where
+    C: DoubleEndedIterator<Item = I>,
+    I: DoubleEndedIterator,
+{
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::chunked::*;
+
+    #[test]
+    pub fn split_and_merge() {
+        let mut list = ChunkList::from_chunks(vec![vec![1, 2, 3, 4]]);
+        list.insert((0, 4), 5, 4);
+        assert_eq!(list.chunks, vec![vec![1, 2], vec![3, 4, 5]]);
+        assert_eq!((list.offset((1, 0)), list.len()), (2, 5));
+
+        assert_eq!(list.remove((0, 0), 4), 1);
+        assert_eq!(list.chunks, vec![vec![2, 3, 4, 5]]);
+        assert_eq!((list.offset((1, 0)), list.len()), (4, 4));
+
+        list.insert((0, 0), 0, 4);
+        list.insert((0, 0), -1, 4);
+        assert_eq!(list.chunks, vec![vec![-1, 0, 2], vec![3, 4, 5]]);
+        assert_eq!((list.offset((1, 1)), list.len()), (4, 6));
+        assert_eq!(list.remove((0, 0), 4), -1);
+        assert_eq!(list.remove((0, 0), 4), 0);
+        assert_eq!(list.chunks, vec![vec![2, 3, 4, 5]]);
+
+        for _ in 0..4 {
+            list.remove((0, 0), 4);
+        }
+        assert!(list.chunks.is_empty() && list.counts.is_empty() && list.len() == 0);
+    }
+
+    #[test]
+    pub fn counts_match_chunk_lengths() {
+        let chunks = (0..13).map(|c| (0..c % 4 + 1).collect()).collect();
+        let mut list: ChunkList<Vec<u32>> = ChunkList::from_chunks(chunks);
+        list.insert((6, 0), 0, 8);
+        list.remove((11, 0), 8);
+        let mut start = 0;
+        for (c, chunk) in list.chunks.iter().enumerate() {
+            assert_eq!(list.offset((c, 1)), start + 1);
+            start += chunk.len();
+        }
+        assert_eq!(
+            (list.offset((list.chunks.len(), 0)), list.len()),
+            (start, start)
+        );
+    }
+
+    #[test]
+    pub fn find_positions() {
+        let list = ChunkList::from_chunks(vec![vec![1, 3], vec![5, 7]]);
+        assert_eq!(list.find(&3), Ok((0, 1)));
+        assert_eq!(list.find(&4), Err((1, 0)));
+        assert_eq!(list.find(&0), Err((0, 0)));
+        assert_eq!(list.find(&8), Err((1, 2)));
+        assert_eq!(ChunkList::<Vec<i32>>::new().find(&8), Err((0, 0)));
+        assert_eq!(list.offset(list.locate(|&x| x < 6)), 3);
+        assert_eq!(list.offset((2, 0)), 4);
+    }
+}
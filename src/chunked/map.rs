@@ -0,0 +1,686 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::{FusedIterator, Zip};
+use std::ops::RangeBounds;
+use std::{mem, slice, vec};
+
+use cc_traits::{
+    covariant_item_mut, covariant_item_ref, covariant_key_ref, simple_collection_mut,
+    simple_collection_ref, simple_keyed_ref, Clear, Collection, CollectionMut, CollectionRef, Get,
+    GetKeyValue, GetKeyValueMut, GetMut, Keyed, KeyedRef, Len, MapInsert, MapIter, MapIterMut,
+    Remove, SimpleCollectionMut, SimpleCollectionRef, SimpleKeyedRef,
+};
+
+use crate::chunked::{Chunk, ChunkList, Chunks, ChunksMut, Flat, Position};
+use crate::compare::Natural;
+use crate::map::BinaryMap;
+
+/// One chunk of a [`ChunkedBinaryMap`]: parallel sorted `Vec`s of keys and values.
+#[derive(Debug, Clone)]
+struct Pairs<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+}
+
+impl<K, V> Default for Pairs<K, V> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<K, V> Chunk for Pairs<K, V> {
+    type Key = K;
+    type Item = (K, V);
+
+    fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    fn insert(&mut self, index: usize, (key, value): (K, V)) {
+        self.keys.insert(index, key);
+        self.values.insert(index, value);
+    }
+
+    /// Removes both halves of the entry before either can be dropped.
+    fn remove(&mut self, index: usize) -> (K, V) {
+        (self.keys.remove(index), self.values.remove(index))
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        Self {
+            keys: self.keys.split_off(at),
+            values: self.values.split_off(at),
+        }
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.keys.append(&mut other.keys);
+        self.values.append(&mut other.values);
+    }
+}
+
+type PairsIter<'a, K, V> = Zip<slice::Iter<'a, K>, slice::Iter<'a, V>>;
+type PairsIterMut<'a, K, V> = Zip<slice::Iter<'a, K>, slice::IterMut<'a, V>>;
+type FlatPairs<'a, K, V> = Flat<Chunks<'a, Pairs<K, V>, PairsIter<'a, K, V>>, PairsIter<'a, K, V>>;
+type FlatPairsMut<'a, K, V> =
+    Flat<ChunksMut<'a, Pairs<K, V>, PairsIterMut<'a, K, V>>, PairsIterMut<'a, K, V>>;
+
+/// A sorted map stored as a list of sorted chunks of at most `CHUNK` entries.
+#[derive(Clone)]
+pub struct ChunkedBinaryMap<K, V, const CHUNK: usize = 1024> {
+    list: ChunkList<Pairs<K, V>>,
+}
+
+impl<K, V, const CHUNK: usize> ChunkedBinaryMap<K, V, CHUNK> {
+    const NONZERO: () = assert!(CHUNK > 0, "chunks must hold at least one entry");
+
+    /// Makes a new, empty map.
+    pub const fn new() -> Self {
+        let () = Self::NONZERO;
+        Self {
+            list: ChunkList::new(),
+        }
+    }
+
+    /// Iterates over the entries in order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.between((0, 0), (self.list.chunks.len(), 0))
+    }
+
+    /// Iterates over the entries in order, with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.between_mut((0, 0), (self.list.chunks.len(), 0))
+    }
+
+    /// Iterates over the values in order of their keys.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Iterates in order over the entries whose keys lie within `range`.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.list.range(&range);
+        self.between(start, end)
+    }
+
+    /// Like `range`, with mutable references to the values.
+    pub fn range_mut<Q, R>(&mut self, range: R) -> IterMut<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.list.range(&range);
+        self.between_mut(start, end)
+    }
+
+    fn between(&self, start: Position, end: Position) -> Iter<'_, K, V> {
+        let ((a, i), (b, j), chunks) = (start, end, &self.list.chunks);
+        let (keys, values): (&[K], &[V]) = (&[], &[]);
+        let empty = keys.iter().zip(values);
+        let (front, middle, back) = if a == b {
+            let front = chunks.get(a).map_or(empty.clone(), |chunk| {
+                chunk.keys[i..j].iter().zip(&chunk.values[i..j])
+            });
+            (front, &chunks[..0], empty)
+        } else {
+            let chunk = &chunks[a];
+            let front = chunk.keys[i..].iter().zip(&chunk.values[i..]);
+            let back = chunks.get(b).map_or(empty, |chunk| {
+                chunk.keys[..j].iter().zip(&chunk.values[..j])
+            });
+            (front, &chunks[a + 1..b], back)
+        };
+        let len = self.list.offset(end) - self.list.offset(start);
+        Iter {
+            inner: Flat {
+                front,
+                chunks: middle
+                    .iter()
+                    .map(|chunk| chunk.keys.iter().zip(&chunk.values)),
+                back,
+                len,
+            },
+        }
+    }
+
+    fn between_mut(&mut self, start: Position, end: Position) -> IterMut<'_, K, V> {
+        let len = self.list.offset(end) - self.list.offset(start);
+        let ((a, i), (b, j), chunks) = (start, end, &mut self.list.chunks);
+        let (keys, values): (&[K], &mut [V]) = (&[], &mut []);
+        let empty = keys.iter().zip(values);
+        let (front, middle, back) = if a == b {
+            let front = chunks.get_mut(a).map_or(empty, |chunk| {
+                chunk.keys[i..j].iter().zip(&mut chunk.values[i..j])
+            });
+            let none: &mut [Pairs<K, V>] = &mut [];
+            (front, none, [].iter().zip(&mut []))
+        } else {
+            let (head, tail) = chunks.split_at_mut(a + 1);
+            let (middle, rest) = tail.split_at_mut(b - a - 1);
+            let chunk = &mut head[a];
+            let front = chunk.keys[i..].iter().zip(&mut chunk.values[i..]);
+            let back = rest.first_mut().map_or(empty, |chunk| {
+                chunk.keys[..j].iter().zip(&mut chunk.values[..j])
+            });
+            (front, middle, back)
+        };
+        IterMut {
+            inner: Flat {
+                front,
+                chunks: middle
+                    .iter_mut()
+                    .map(|chunk| chunk.keys.iter().zip(&mut chunk.values)),
+                back,
+                len,
+            },
+        }
+    }
+}
+
+impl<K, V, const CHUNK: usize> Default for ChunkedBinaryMap<K, V, CHUNK> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const CHUNK: usize> fmt::Debug
+    for ChunkedBinaryMap<K, V, CHUNK>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, const CHUNK: usize> PartialEq for ChunkedBinaryMap<K, V, CHUNK> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq, const CHUNK: usize> Eq for ChunkedBinaryMap<K, V, CHUNK> {}
+
+impl<K, V, const CHUNK: usize> From<BinaryMap<K, V>> for ChunkedBinaryMap<K, V, CHUNK> {
+    /// Fills the chunks halfway, leaving room for inserts.
+    fn from(map: BinaryMap<K, V>) -> Self {
+        let (keys, values) = map.into_parts();
+        let len = keys.len();
+        let size = CHUNK.div_ceil(2);
+        let (mut keys, mut values) = (keys.into_iter(), values.into_iter());
+        let mut chunks = Vec::with_capacity(len.div_ceil(size));
+        loop {
+            let chunk = Pairs {
+                keys: keys.by_ref().take(size).collect(),
+                values: values.by_ref().take(size).collect(),
+            };
+            if chunk.keys.is_empty() {
+                break;
+            }
+            chunks.push(chunk);
+        }
+        Self {
+            list: ChunkList::from_chunks(chunks),
+        }
+    }
+}
+
+impl<K, V, const CHUNK: usize> From<ChunkedBinaryMap<K, V, CHUNK>> for BinaryMap<K, V> {
+    fn from(map: ChunkedBinaryMap<K, V, CHUNK>) -> Self {
+        let mut keys = Vec::with_capacity(map.len());
+        let mut values = Vec::with_capacity(map.len());
+        for mut chunk in map.list.chunks {
+            keys.append(&mut chunk.keys);
+            values.append(&mut chunk.values);
+        }
+        BinaryMap::from_sorted_parts_unchecked(keys, values, Natural)
+    }
+}
+
+impl<K: Ord, V, const CHUNK: usize> FromIterator<(K, V)> for ChunkedBinaryMap<K, V, CHUNK> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        BinaryMap::from_iter(iter).into()
+    }
+}
+
+impl<K: Ord, V, const CHUNK: usize> Extend<(K, V)> for ChunkedBinaryMap<K, V, CHUNK> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, const CHUNK: usize> IntoIterator for ChunkedBinaryMap<K, V, CHUNK> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            len: self.len(),
+            inner: self
+                .list
+                .chunks
+                .into_iter()
+                .flat_map(|chunk| chunk.keys.into_iter().zip(chunk.values)),
+        }
+    }
+}
+
+impl<'a, K, V, const CHUNK: usize> IntoIterator for &'a ChunkedBinaryMap<K, V, CHUNK> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const CHUNK: usize> IntoIterator for &'a mut ChunkedBinaryMap<K, V, CHUNK> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const CHUNK: usize> Collection for ChunkedBinaryMap<K, V, CHUNK> {
+    type Item = V;
+}
+
+impl<K, V, const CHUNK: usize> CollectionRef for ChunkedBinaryMap<K, V, CHUNK> {
+    type ItemRef<'a>
+        = &'a V
+    where
+        Self: 'a;
+
+    covariant_item_ref!();
+}
+
+impl<K, V, const CHUNK: usize> CollectionMut for ChunkedBinaryMap<K, V, CHUNK> {
+    type ItemMut<'a>
+        = &'a mut V
+    where
+        Self: 'a;
+
+    covariant_item_mut!();
+}
+
+impl<K, V, const CHUNK: usize> SimpleCollectionRef for ChunkedBinaryMap<K, V, CHUNK> {
+    simple_collection_ref!();
+}
+
+impl<K, V, const CHUNK: usize> SimpleCollectionMut for ChunkedBinaryMap<K, V, CHUNK> {
+    simple_collection_mut!();
+}
+
+impl<K, V, const CHUNK: usize> Keyed for ChunkedBinaryMap<K, V, CHUNK> {
+    type Key = K;
+}
+
+impl<K, V, const CHUNK: usize> KeyedRef for ChunkedBinaryMap<K, V, CHUNK> {
+    type KeyRef<'a>
+        = &'a K
+    where
+        Self: 'a;
+
+    covariant_key_ref!();
+}
+
+impl<K, V, const CHUNK: usize> SimpleKeyedRef for ChunkedBinaryMap<K, V, CHUNK> {
+    simple_keyed_ref!();
+}
+
+impl<K, V, const CHUNK: usize> Len for ChunkedBinaryMap<K, V, CHUNK> {
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const CHUNK: usize> Get<&'a Q>
+    for ChunkedBinaryMap<K, V, CHUNK>
+{
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let (c, i) = self.list.find(key).ok()?;
+        Some(&self.list.chunks[c].values[i])
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const CHUNK: usize> GetMut<&'a Q>
+    for ChunkedBinaryMap<K, V, CHUNK>
+{
+    fn get_mut(&mut self, key: &'a Q) -> Option<Self::ItemMut<'_>> {
+        let (c, i) = self.list.find(key).ok()?;
+        Some(&mut self.list.chunks[c].values[i])
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const CHUNK: usize> GetKeyValue<&'a Q>
+    for ChunkedBinaryMap<K, V, CHUNK>
+{
+    fn get_key_value(&self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemRef<'_>)> {
+        let (c, i) = self.list.find(key).ok()?;
+        let chunk = &self.list.chunks[c];
+        Some((&chunk.keys[i], &chunk.values[i]))
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const CHUNK: usize> GetKeyValueMut<&'a Q>
+    for ChunkedBinaryMap<K, V, CHUNK>
+{
+    fn get_key_value_mut(&mut self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemMut<'_>)> {
+        let (c, i) = self.list.find(key).ok()?;
+        let chunk = &mut self.list.chunks[c];
+        Some((&chunk.keys[i], &mut chunk.values[i]))
+    }
+}
+
+impl<K: Ord, V, const CHUNK: usize> MapInsert<K> for ChunkedBinaryMap<K, V, CHUNK> {
+    type Output = Option<V>;
+
+    fn insert(&mut self, key: K, value: Self::Item) -> Self::Output {
+        match self.list.find(&key) {
+            Ok((c, i)) => Some(mem::replace(&mut self.list.chunks[c].values[i], value)),
+            Err(position) => {
+                self.list.insert(position, (key, value), CHUNK);
+                None
+            }
+        }
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V, const CHUNK: usize> Remove<&'a Q>
+    for ChunkedBinaryMap<K, V, CHUNK>
+{
+    fn remove(&mut self, key: &'a Q) -> Option<Self::Item> {
+        let position = self.list.find(key).ok()?;
+        let (_, value) = self.list.remove(position, CHUNK);
+        Some(value)
+    }
+}
+
+impl<K, V, const CHUNK: usize> Clear for ChunkedBinaryMap<K, V, CHUNK> {
+    fn clear(&mut self) {
+        self.list.clear()
+    }
+}
+
+impl<K, V, const CHUNK: usize> cc_traits::Iter for ChunkedBinaryMap<K, V, CHUNK> {
+    type Iter<'a>
+        = Values<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.values()
+    }
+}
+
+impl<K, V, const CHUNK: usize> MapIter for ChunkedBinaryMap<K, V, CHUNK> {
+    type Iter<'a>
+        = Iter<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+impl<K, V, const CHUNK: usize> MapIterMut for ChunkedBinaryMap<K, V, CHUNK> {
+    type IterMut<'a>
+        = IterMut<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.iter_mut()
+    }
+}
+
+/// Iterator over the entries of a [`ChunkedBinaryMap`], returned by `iter` and `range`.
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    inner: FlatPairs<'a, K, V>,
+}
+
+/// Mutable iterator over the entries of a [`ChunkedBinaryMap`], returned by `iter_mut` and `range_mut`.
+#[derive(Debug)]
+pub struct IterMut<'a, K, V> {
+    inner: FlatPairsMut<'a, K, V>,
+}
+
+/// Iterator over the values of a [`ChunkedBinaryMap`], returned by `values`.
+#[derive(Debug)]
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+/// Owning iterator over the entries of a [`ChunkedBinaryMap`], returned by `into_iter`.
+pub struct IntoIter<K, V> {
+    #[allow(clippy::type_complexity)]
+    inner: std::iter::FlatMap<
+        vec::IntoIter<Pairs<K, V>>,
+        Zip<vec::IntoIter<K>, vec::IntoIter<V>>,
+        fn(Pairs<K, V>) -> Zip<vec::IntoIter<K>, vec::IntoIter<V>>,
+    >,
+    len: usize,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for IntoIter<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIter").field("len", &self.len).finish()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.inner.next()?.1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(self.inner.next_back()?.1)
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.inner.next()?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = self.inner.next_back()?;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::chunked::map::*;
+    use crate::test_support::{assert_lockstep_after, Brittle};
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    #[test]
+    pub fn insert_remove_matches_btree_map() {
+        let mut map = ChunkedBinaryMap::<u32, u32, 4>::new();
+        let mut btree = BTreeMap::new();
+        for (n, key) in (0..200).map(|x: u32| x.wrapping_mul(37) % 101).enumerate() {
+            assert_eq!(map.insert(key, n as u32), btree.insert(key, n as u32));
+        }
+        assert_eq!(map.len(), btree.len());
+        assert!(map.iter().eq(btree.iter()));
+        assert!(map.list.chunks.iter().all(|chunk| chunk.keys.len() <= 4));
+
+        for key in (0..150).map(|x: u32| x.wrapping_mul(53) % 103) {
+            assert_eq!(map.remove(&key), btree.remove(&key));
+            assert_eq!(map.len(), btree.len());
+            assert!(map.iter().eq(btree.iter()));
+            assert!(map.list.chunks.iter().all(|chunk| !chunk.keys.is_empty()));
+        }
+        for key in 0..110 {
+            assert_eq!(map.get_key_value(&key), btree.get_key_value(&key));
+        }
+    }
+
+    #[test]
+    pub fn range_matches_btree_map() {
+        let entries = [1, 3, 4, 7, 8, 9, 12, 15, 20, 21].map(|k| (k, k * 10));
+        let mut map = ChunkedBinaryMap::<i32, i32, 3>::from_iter(entries);
+        let mut btree = BTreeMap::from_iter(entries);
+
+        for a in 0..23 {
+            for b in a..23 {
+                for bounds in [
+                    (Bound::Included(a), Bound::Included(b)),
+                    (Bound::Included(a), Bound::Excluded(b)),
+                    (Bound::Excluded(a), Bound::Included(b)),
+                    (Bound::Unbounded, Bound::Included(b)),
+                    (Bound::Included(a), Bound::Unbounded),
+                ] {
+                    let expected: Vec<_> = btree.range(bounds).collect();
+                    assert_eq!(map.range(bounds).collect::<Vec<_>>(), expected);
+                    assert_eq!(map.range(bounds).len(), expected.len());
+                    assert_eq!(
+                        map.range(bounds).rev().collect::<Vec<_>>(),
+                        expected.into_iter().rev().collect::<Vec<_>>()
+                    );
+
+                    for (_, v) in map.range_mut(bounds) {
+                        *v += 1;
+                    }
+                    for (_, v) in btree.range_mut(bounds) {
+                        *v += 1;
+                    }
+                    assert!(map.iter().eq(btree.iter()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn remove_panicking_key_drop() {
+        let mut map =
+            ChunkedBinaryMap::<_, _, 4>::from_iter((0..10).map(|k| (Brittle(k, k == 4), k)));
+        assert_lockstep_after(
+            &mut map,
+            |map| {
+                map.remove(&4);
+            },
+            |k| k.0,
+            "dropped a brittle key",
+        );
+        assert_eq!(map.len(), 9);
+        assert!(map.iter().all(|(key, _)| key.0 != 4));
+        assert_eq!(map.get(&5), Some(&5));
+    }
+
+    #[test]
+    pub fn round_trip() {
+        let binary = BinaryMap::from_iter([(3, 'c'), (1, 'a'), (2, 'b'), (5, 'e'), (4, 'd')]);
+        let mut map = ChunkedBinaryMap::<_, _, 2>::from(binary.clone());
+        assert_eq!(map.get(&3), Some(&'c'));
+        assert_eq!(map.values().rev().collect::<String>(), "edcba".to_string());
+        assert_eq!(
+            format!("{map:?}"),
+            "{1: 'a', 2: 'b', 3: 'c', 4: 'd', 5: 'e'}"
+        );
+        assert_eq!(BinaryMap::from(map.clone()), binary);
+
+        let mut into_iter = map.clone().into_iter();
+        assert_eq!(into_iter.next_back(), Some((5, 'e')));
+        assert_eq!(into_iter.len(), 4);
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter_mut().next(), None);
+    }
+}
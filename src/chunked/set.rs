@@ -0,0 +1,326 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::RangeBounds;
+use std::slice;
+
+use cc_traits::{
+    covariant_item_ref, simple_collection_ref, Clear, Collection, CollectionRef, Get, Insert, Len,
+    Remove, SimpleCollectionRef,
+};
+
+use crate::chunked::{ChunkList, Chunks, Flat, Position};
+use crate::compare::Natural;
+use crate::set::BinarySet;
+
+/// A sorted set stored as a list of sorted chunks of at most `CHUNK` elements.
+#[derive(Clone)]
+pub struct ChunkedBinarySet<T, const CHUNK: usize = 1024> {
+    list: ChunkList<Vec<T>>,
+}
+
+impl<T, const CHUNK: usize> ChunkedBinarySet<T, CHUNK> {
+    const NONZERO: () = assert!(CHUNK > 0, "chunks must hold at least one element");
+
+    /// Makes a new, empty set.
+    pub const fn new() -> Self {
+        let () = Self::NONZERO;
+        Self {
+            list: ChunkList::new(),
+        }
+    }
+
+    /// Iterates over the elements in order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.between((0, 0), (self.list.chunks.len(), 0))
+    }
+
+    /// Iterates in order over the elements within `range`.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.list.range(&range);
+        self.between(start, end)
+    }
+
+    fn between(&self, start: Position, end: Position) -> Iter<'_, T> {
+        let ((a, i), (b, j), chunks) = (start, end, &self.list.chunks);
+        let empty: &[T] = &[];
+        let (front, middle, back) = if a == b {
+            let front = chunks.get(a).map_or(empty, |chunk| &chunk[i..j]);
+            (front, &chunks[..0], empty)
+        } else {
+            let back = chunks.get(b).map_or(empty, |chunk| &chunk[..j]);
+            (&chunks[a][i..], &chunks[a + 1..b], back)
+        };
+        let len = self.list.offset(end) - self.list.offset(start);
+        Iter {
+            inner: Flat {
+                front: front.iter(),
+                chunks: middle.iter().map(|chunk| chunk.iter()),
+                back: back.iter(),
+                len,
+            },
+        }
+    }
+}
+
+impl<T, const CHUNK: usize> Default for ChunkedBinarySet<T, CHUNK> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, const CHUNK: usize> fmt::Debug for ChunkedBinarySet<T, CHUNK> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const CHUNK: usize> PartialEq for ChunkedBinarySet<T, CHUNK> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, const CHUNK: usize> Eq for ChunkedBinarySet<T, CHUNK> {}
+
+impl<T, const CHUNK: usize> From<BinarySet<T>> for ChunkedBinarySet<T, CHUNK> {
+    /// Fills the chunks halfway, leaving room for inserts.
+    fn from(set: BinarySet<T>) -> Self {
+        let sorted: Vec<T> = set.into();
+        let len = sorted.len();
+        let size = CHUNK.div_ceil(2);
+        let mut elements = sorted.into_iter();
+        let mut chunks = Vec::with_capacity(len.div_ceil(size));
+        loop {
+            let chunk: Vec<T> = elements.by_ref().take(size).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            chunks.push(chunk);
+        }
+        Self {
+            list: ChunkList::from_chunks(chunks),
+        }
+    }
+}
+
+impl<T, const CHUNK: usize> From<ChunkedBinarySet<T, CHUNK>> for BinarySet<T> {
+    fn from(set: ChunkedBinarySet<T, CHUNK>) -> Self {
+        BinarySet::from_sorted_vec_unchecked(set.into_iter().collect(), Natural)
+    }
+}
+
+impl<T: Ord, const CHUNK: usize> FromIterator<T> for ChunkedBinarySet<T, CHUNK> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        BinarySet::from_iter(iter).into()
+    }
+}
+
+impl<T: Ord, const CHUNK: usize> Extend<T> for ChunkedBinarySet<T, CHUNK> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for element in iter {
+            self.insert(element);
+        }
+    }
+}
+
+impl<T, const CHUNK: usize> IntoIterator for ChunkedBinarySet<T, CHUNK> {
+    type Item = T;
+    type IntoIter = std::iter::Flatten<std::vec::IntoIter<Vec<T>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.chunks.into_iter().flatten()
+    }
+}
+
+impl<'a, T, const CHUNK: usize> IntoIterator for &'a ChunkedBinarySet<T, CHUNK> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, const CHUNK: usize> Collection for ChunkedBinarySet<T, CHUNK> {
+    type Item = T;
+}
+
+impl<T, const CHUNK: usize> CollectionRef for ChunkedBinarySet<T, CHUNK> {
+    type ItemRef<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    covariant_item_ref!();
+}
+
+impl<T, const CHUNK: usize> SimpleCollectionRef for ChunkedBinarySet<T, CHUNK> {
+    simple_collection_ref!();
+}
+
+impl<T, const CHUNK: usize> Len for ChunkedBinarySet<T, CHUNK> {
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, T: Borrow<Q>, const CHUNK: usize> Get<&'a Q>
+    for ChunkedBinarySet<T, CHUNK>
+{
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let (c, i) = self.list.find(key).ok()?;
+        Some(&self.list.chunks[c][i])
+    }
+}
+
+impl<T: Ord, const CHUNK: usize> Insert for ChunkedBinarySet<T, CHUNK> {
+    type Output = bool;
+
+    fn insert(&mut self, element: Self::Item) -> Self::Output {
+        match self.list.find(&element) {
+            Ok(_) => false,
+            Err(position) => {
+                self.list.insert(position, element, CHUNK);
+                true
+            }
+        }
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, T: Borrow<Q>, const CHUNK: usize> Remove<&'a Q>
+    for ChunkedBinarySet<T, CHUNK>
+{
+    fn remove(&mut self, key: &'a Q) -> Option<Self::Item> {
+        let position = self.list.find(key).ok()?;
+        Some(self.list.remove(position, CHUNK))
+    }
+}
+
+impl<T, const CHUNK: usize> Clear for ChunkedBinarySet<T, CHUNK> {
+    fn clear(&mut self) {
+        self.list.clear()
+    }
+}
+
+impl<T, const CHUNK: usize> cc_traits::Iter for ChunkedBinarySet<T, CHUNK> {
+    type Iter<'a>
+        = Iter<'a, T>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+/// In-order iterator over a [`ChunkedBinarySet`], returned by `iter` and `range`.
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    inner: Flat<Chunks<'a, Vec<T>, slice::Iter<'a, T>>, slice::Iter<'a, T>>,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::chunked::set::*;
+    use std::collections::BTreeSet;
+    use std::ops::Bound;
+
+    #[test]
+    pub fn insert_remove_matches_btree_set() {
+        let mut set = ChunkedBinarySet::<u32, 4>::new();
+        let mut btree = BTreeSet::new();
+        for x in (0..200).map(|x: u32| x.wrapping_mul(37) % 101) {
+            assert_eq!(set.insert(x), btree.insert(x));
+        }
+        assert_eq!(set.len(), btree.len());
+        assert!(set.iter().eq(btree.iter()));
+        assert!(set.list.chunks.iter().all(|chunk| chunk.len() <= 4));
+
+        for x in (0..150).map(|x: u32| x.wrapping_mul(53) % 103) {
+            assert_eq!(set.remove(&x), btree.take(&x));
+            assert_eq!(set.len(), btree.len());
+            assert!(set.iter().eq(btree.iter()));
+            assert!(set.list.chunks.iter().all(|chunk| !chunk.is_empty()));
+            let rebuilt = ChunkList::from_chunks(set.list.chunks.clone());
+            assert_eq!(rebuilt.counts, set.list.counts);
+        }
+        for x in 0..110 {
+            assert_eq!(set.get(&x), btree.get(&x));
+        }
+    }
+
+    #[test]
+    pub fn range_matches_btree_set() {
+        let values = [1, 3, 4, 7, 8, 9, 12, 15, 20, 21];
+        let set = ChunkedBinarySet::<i32, 3>::from_iter(values);
+        let btree = BTreeSet::from_iter(values);
+
+        for a in 0..23 {
+            for b in a..23 {
+                for bounds in [
+                    (Bound::Included(a), Bound::Included(b)),
+                    (Bound::Included(a), Bound::Excluded(b)),
+                    (Bound::Excluded(a), Bound::Included(b)),
+                    (Bound::Unbounded, Bound::Included(b)),
+                    (Bound::Included(a), Bound::Unbounded),
+                ] {
+                    let expected: Vec<_> = btree.range(bounds).collect();
+                    assert_eq!(set.range(bounds).collect::<Vec<_>>(), expected);
+                    assert_eq!(set.range(bounds).len(), expected.len());
+                    assert_eq!(
+                        set.range(bounds).rev().collect::<Vec<_>>(),
+                        expected.into_iter().rev().collect::<Vec<_>>()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn round_trip() {
+        let binary = BinarySet::from_iter(["d", "a", "c", "b", "e"]);
+        let mut set = ChunkedBinarySet::<_, 2>::from(binary.clone());
+        assert_eq!(set.get("c"), Some(&"c"));
+        assert_eq!(format!("{set:?}"), r#"{"a", "b", "c", "d", "e"}"#);
+        assert_eq!(BinarySet::from(set.clone()), binary);
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.iter().next(), None);
+    }
+}
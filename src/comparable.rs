@@ -0,0 +1,60 @@
+//! Traits for looking up keys with a type other than the stored key, in the style of the
+//! [`equivalent`](https://crates.io/crates/equivalent) crate.
+//!
+//! Every `Q: Ord` is `Comparable<K>` whenever `K: Borrow<Q>`. For queries that cannot be
+//! borrowed from the key, such as `(u32, &str)` against `(u32, String)`, implement
+//! [`Comparable`] on a query type of your own:
+//!
+//! ```
+//! # #[cfg(feature = "std")] {
+//! use std::cmp::Ordering;
+//! use binary_tree_collections::comparable::{Comparable, Equivalent};
+//! use binary_tree_collections::map::BinaryMap;
+//!
+//! struct Query<'a>(u32, &'a str);
+//!
+//! impl Equivalent<(u32, String)> for Query<'_> {
+//!     fn equivalent(&self, key: &(u32, String)) -> bool {
+//!         self.0 == key.0 && self.1 == key.1
+//!     }
+//! }
+//!
+//! impl Comparable<(u32, String)> for Query<'_> {
+//!     fn compare(&self, key: &(u32, String)) -> Ordering {
+//!         self.0.cmp(&key.0).then_with(|| self.1.cmp(key.1.as_str()))
+//!     }
+//! }
+//!
+//! let map = BinaryMap::from_iter([((1, "a".to_string()), 'x'), ((2, "b".to_string()), 'y')]);
+//! assert_eq!(map.get_by(&Query(2, "b")), Some(&'y'));
+//! # }
+//! ```
+
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+/// Key equivalence, used to check whether a query matches a stored key.
+pub trait Equivalent<K: ?Sized> {
+    fn equivalent(&self, key: &K) -> bool;
+}
+
+/// Key ordering, used to search for a query among sorted keys.
+///
+/// Must be consistent with the ordering of the keys: if `a < b` then
+/// `q.compare(a)` must not be less than `q.compare(b)`.
+pub trait Comparable<K: ?Sized>: Equivalent<K> {
+    /// Compares the query with `key`.
+    fn compare(&self, key: &K) -> Ordering;
+}
+
+impl<Q: ?Sized + Eq, K: ?Sized + Borrow<Q>> Equivalent<K> for Q {
+    fn equivalent(&self, key: &K) -> bool {
+        PartialEq::eq(self, key.borrow())
+    }
+}
+
+impl<Q: ?Sized + Ord, K: ?Sized + Borrow<Q>> Comparable<K> for Q {
+    fn compare(&self, key: &K) -> Ordering {
+        Ord::cmp(self, key.borrow())
+    }
+}
@@ -0,0 +1,82 @@
+//! Comparators that decide the order of a [`BinarySetBy`](crate::set::BinarySetBy) or
+//! [`BinaryMapBy`](crate::map::BinaryMapBy).
+//!
+//! ```
+//! # #[cfg(feature = "std")] {
+//! use binary_tree_collections::compare::{CaseInsensitive, Natural, Reverse};
+//! use binary_tree_collections::set::BinarySetBy;
+//! use cc_traits::Get;
+//!
+//! let set = BinarySetBy::from_iter_with_comparator(["b", "A", "a", "C"], CaseInsensitive);
+//! assert_eq!(set.as_ref(), &vec!["A", "b", "C"]);
+//! assert_eq!(set.get("B"), Some(&"b"));
+//!
+//! let set = BinarySetBy::from_iter_with_comparator([1, 3, 2], Reverse(Natural));
+//! assert_eq!(set.as_ref(), &vec![3, 2, 1]);
+//! # }
+//! ```
+
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+/// A total order, comparing a query of type `L` with an element of type `R`.
+///
+/// Comparing elements with elements uses `Compare<T>`. Lookups with a query `Q`
+/// also need `Compare<Q, T>`, which must agree with the order of the elements.
+pub trait Compare<L: ?Sized, R: ?Sized = L> {
+    fn compare(&self, l: &L, r: &R) -> Ordering;
+}
+
+/// The natural order of `Ord`. Queries can be anything the elements can be borrowed as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Natural;
+
+impl<Q: ?Sized + Ord, T: ?Sized + Borrow<Q>> Compare<Q, T> for Natural {
+    fn compare(&self, l: &Q, r: &T) -> Ordering {
+        l.cmp(r.borrow())
+    }
+}
+
+/// The reverse of another comparator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Reverse<C = Natural>(pub C);
+
+impl<L: ?Sized, R: ?Sized, C: Compare<L, R>> Compare<L, R> for Reverse<C> {
+    fn compare(&self, l: &L, r: &R) -> Ordering {
+        self.0.compare(l, r).reverse()
+    }
+}
+
+/// Orders floats with `total_cmp`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TotalOrder;
+
+impl Compare<f32> for TotalOrder {
+    fn compare(&self, l: &f32, r: &f32) -> Ordering {
+        l.total_cmp(r)
+    }
+}
+
+impl Compare<f64> for TotalOrder {
+    fn compare(&self, l: &f64, r: &f64) -> Ordering {
+        l.total_cmp(r)
+    }
+}
+
+/// Orders strings by their lowercase characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CaseInsensitive;
+
+impl<L: ?Sized + AsRef<str>, R: ?Sized + AsRef<str>> Compare<L, R> for CaseInsensitive {
+    fn compare(&self, l: &L, r: &R) -> Ordering {
+        let l = l.as_ref().chars().flat_map(char::to_lowercase);
+        let r = r.as_ref().chars().flat_map(char::to_lowercase);
+        l.cmp(r)
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Compare<T> for F {
+    fn compare(&self, l: &T, r: &T) -> Ordering {
+        self(l, r)
+    }
+}
@@ -0,0 +1,167 @@
+//! Scenarios run through the `cc_traits` abstractions against our collections and the std ones,
+//! so generic code sees the same behaviour from each.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Deref;
+use std::vec::Vec;
+
+use cc_traits::*;
+
+use crate::chunked::{ChunkedBinaryMap, ChunkedBinarySet};
+use crate::map::{BinaryMap, SmallBinaryMap};
+use crate::pair_map::BinaryPairMap;
+use crate::set::{BinarySet, SmallBinarySet};
+
+fn map_insert_get_remove<M: MapMut<u32, String> + Default>() {
+    let mut map = M::default();
+    assert!(map.is_empty());
+
+    assert_eq!(map.insert(2, "b".to_string()), None);
+    assert_eq!(map.insert(1, "a".to_string()), None);
+    assert_eq!(map.insert(2, "B".to_string()), Some("b".to_string()));
+    assert_eq!(map.len(), 2);
+
+    assert_eq!(map.get(&2).as_deref(), Some(&"B".to_string()));
+    assert!(map.contains(&1));
+    assert!(!map.contains(&3));
+
+    {
+        let (k, v) = map.get_key_value(&1).unwrap();
+        assert_eq!((*k, v.as_str()), (1, "a"));
+    }
+
+    map.get_mut(&1).unwrap().push('!');
+    assert_eq!(map.get(&1).as_deref(), Some(&"a!".to_string()));
+
+    assert_eq!(map.remove(&1), Some("a!".to_string()));
+    assert_eq!(map.remove(&1), None);
+    assert_eq!(map.len(), 1);
+}
+
+fn map_iter<M>()
+where
+    M: MapMut<u32, u32> + MapIter + MapIterMut + Iter + Clear + Default,
+    for<'a> M::ItemRef<'a>: Deref<Target = u32>,
+{
+    let mut map = M::default();
+    for k in [3, 1, 2] {
+        map.insert(k, k * 10);
+    }
+
+    for (_, mut v) in MapIterMut::iter_mut(&mut map) {
+        *v += 1;
+    }
+
+    let mut pairs: Vec<(u32, u32)> = MapIter::iter(&map).map(|(k, v)| (*k, *v)).collect();
+    pairs.sort();
+    assert_eq!(pairs, vec![(1, 11), (2, 21), (3, 31)]);
+
+    let mut values: Vec<u32> = Iter::iter(&map).map(|v| *v).collect();
+    values.sort();
+    assert_eq!(values, vec![11, 21, 31]);
+
+    map.clear();
+    assert!(map.is_empty());
+}
+
+fn map_borrowed_keys<M>()
+where
+    M: MapMut<String, u32> + for<'a> Get<&'a str> + for<'a> Remove<&'a str> + Default,
+{
+    let mut map = M::default();
+    map.insert("a".to_string(), 1);
+    map.insert("b".to_string(), 2);
+
+    assert_eq!(map.get("b").as_deref(), Some(&2));
+    assert_eq!(Remove::<&str>::remove(&mut map, "a"), Some(1));
+    assert!(!map.contains("a"));
+}
+
+fn set_insert_get_remove<S: SetMut<u32> + Default>() {
+    let mut set = S::default();
+    assert!(set.is_empty());
+
+    assert!(set.insert(2));
+    assert!(set.insert(1));
+    assert!(!set.insert(2));
+    assert_eq!(set.len(), 2);
+
+    assert_eq!(set.get(&1).as_deref(), Some(&1));
+    assert!(set.contains(&2));
+    assert!(!set.contains(&3));
+
+    assert_eq!(set.remove(&1), Some(1));
+    assert_eq!(set.remove(&1), None);
+    assert_eq!(set.len(), 1);
+}
+
+fn set_iter<S>()
+where
+    S: SetMut<u32> + Iter + Clear + Default,
+    for<'a> S::ItemRef<'a>: Deref<Target = u32>,
+{
+    let mut set = S::default();
+    for x in [3, 1, 2, 1] {
+        set.insert(x);
+    }
+
+    let mut values: Vec<u32> = set.iter().map(|x| *x).collect();
+    values.sort();
+    assert_eq!(values, vec![1, 2, 3]);
+
+    set.clear();
+    assert!(set.is_empty());
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::conformance::*;
+
+    #[test]
+    pub fn map_insert_get_remove_conforms() {
+        map_insert_get_remove::<BinaryMap<_, _>>();
+        map_insert_get_remove::<SmallBinaryMap<_, _, 2>>();
+        map_insert_get_remove::<BinaryPairMap<_, _>>();
+        map_insert_get_remove::<ChunkedBinaryMap<_, _, 2>>();
+        map_insert_get_remove::<BTreeMap<_, _>>();
+        map_insert_get_remove::<HashMap<_, _>>();
+    }
+
+    #[test]
+    pub fn map_iter_conforms() {
+        map_iter::<BinaryMap<_, _>>();
+        map_iter::<SmallBinaryMap<_, _, 2>>();
+        map_iter::<BinaryPairMap<_, _>>();
+        map_iter::<ChunkedBinaryMap<_, _, 2>>();
+        map_iter::<BTreeMap<_, _>>();
+        map_iter::<HashMap<_, _>>();
+    }
+
+    #[test]
+    pub fn map_borrowed_keys_conform() {
+        map_borrowed_keys::<BinaryMap<_, _>>();
+        map_borrowed_keys::<SmallBinaryMap<_, _, 2>>();
+        map_borrowed_keys::<BinaryPairMap<_, _>>();
+        map_borrowed_keys::<ChunkedBinaryMap<_, _, 2>>();
+        map_borrowed_keys::<BTreeMap<_, _>>();
+        map_borrowed_keys::<HashMap<_, _>>();
+    }
+
+    #[test]
+    pub fn set_insert_get_remove_conforms() {
+        set_insert_get_remove::<BinarySet<_>>();
+        set_insert_get_remove::<SmallBinarySet<_, 2>>();
+        set_insert_get_remove::<ChunkedBinarySet<_, 2>>();
+        set_insert_get_remove::<BTreeSet<_>>();
+        set_insert_get_remove::<HashSet<_>>();
+    }
+
+    #[test]
+    pub fn set_iter_conforms() {
+        set_iter::<BinarySet<_>>();
+        set_iter::<SmallBinarySet<_, 2>>();
+        set_iter::<ChunkedBinarySet<_, 2>>();
+        set_iter::<BTreeSet<_>>();
+        set_iter::<HashSet<_>>();
+    }
+}
@@ -0,0 +1,53 @@
+//! Errors returned by the fallible constructors.
+
+use core::error::Error;
+use core::fmt;
+
+/// Why input to a `from_sorted_*` constructor was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FromSortedError {
+    /// The element at `index` is less than the one before it.
+    Unsorted { index: usize },
+    /// The element at `index` is equal to the one before it.
+    Duplicate { index: usize },
+    /// There are not as many values as keys.
+    LengthMismatch { keys: usize, values: usize },
+}
+
+impl fmt::Display for FromSortedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsorted { index } => write!(f, "element {index} is out of order"),
+            Self::Duplicate { index } => write!(f, "element {index} is a duplicate"),
+            Self::LengthMismatch { keys, values } => {
+                write!(f, "{keys} keys but {values} values")
+            }
+        }
+    }
+}
+
+impl Error for FromSortedError {}
+
+/// A key that appeared more than once, under [`DuplicatePolicy::Error`](crate::map::DuplicatePolicy::Error).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicateKeyError<K>(pub K);
+
+impl<K: fmt::Debug> fmt::Display for DuplicateKeyError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate key {:?}", self.0)
+    }
+}
+
+impl<K: fmt::Debug> Error for DuplicateKeyError<K> {}
+
+/// An element that was not added because a fixed-capacity collection was full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T>(pub T);
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "collection is full")
+    }
+}
+
+impl<T: fmt::Debug> Error for CapacityError<T> {}
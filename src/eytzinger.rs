@@ -0,0 +1,274 @@
+//! Frozen sets and maps stored in Eytzinger (BFS) order, for read-mostly lookups.
+//!
+//! Node `k`, counting from 1, is stored at index `k - 1` and has children `2k` and `2k + 1`.
+//! Searches walk down the array without branching on comparisons, and the sixteen
+//! descendants four levels down are contiguous, so they are prefetched on the way.
+//!
+//! ```
+//! use binary_tree_collections::eytzinger::EytzingerSet;
+//! use binary_tree_collections::set::BinarySet;
+//! use cc_traits::Get;
+//!
+//! let set = EytzingerSet::from(BinarySet::from_iter([5, 1, 4, 2, 3]));
+//! assert!(set.contains(&4));
+//! assert_eq!(set.range(2..4).collect::<Vec<_>>(), vec![&2, &3]);
+//! assert_eq!(BinarySet::from(set).as_ref(), &vec![1, 2, 3, 4, 5]);
+//! ```
+
+use std::borrow::Borrow;
+use std::iter::FusedIterator;
+use std::ops::{Bound, RangeBounds};
+
+use crate::search;
+
+pub mod map;
+pub mod set;
+
+pub use map::EytzingerMap;
+pub use set::EytzingerSet;
+
+/// How many levels below the current node are prefetched.
+const PREFETCH_LEVELS: u32 = 4;
+
+/// The first node in order, or 0 if there are none.
+fn first(n: usize) -> usize {
+    if n == 0 {
+        return 0;
+    }
+    let mut k = 1;
+    while 2 * k <= n {
+        k *= 2;
+    }
+    k
+}
+
+/// The last node in order, or 0 if there are none.
+fn last(n: usize) -> usize {
+    if n == 0 {
+        return 0;
+    }
+    let mut k = 1;
+    while 2 * k < n {
+        k = 2 * k + 1;
+    }
+    k
+}
+
+/// The node after `k` in order, or 0 if `k` is the last.
+fn next(n: usize, mut k: usize) -> usize {
+    if 2 * k < n {
+        k = 2 * k + 1;
+        while 2 * k <= n {
+            k *= 2;
+        }
+        k
+    } else {
+        k >> (k.trailing_ones() + 1)
+    }
+}
+
+/// The node before `k` in order, or 0 if `k` is the first.
+fn prev(n: usize, mut k: usize) -> usize {
+    if 2 * k <= n {
+        k *= 2;
+        while 2 * k < n {
+            k = 2 * k + 1;
+        }
+        k
+    } else {
+        k >> (k.trailing_zeros() + 1)
+    }
+}
+
+/// Number of nodes in the subtree rooted at `k`.
+fn subtree_len(n: usize, k: usize) -> usize {
+    let (mut lo, mut hi, mut len) = (k, k, 0);
+    while lo <= n {
+        len += hi.min(n) - lo + 1;
+        lo *= 2;
+        hi = 2 * hi + 1;
+    }
+    len
+}
+
+/// Position of node `k` in order, or `n` for node 0.
+fn rank(n: usize, k: usize) -> usize {
+    if k == 0 {
+        return n;
+    }
+    let mut rank = subtree_len(n, 2 * k);
+    let mut node = k;
+    while node > 1 {
+        if node & 1 == 1 {
+            // a right child comes after its parent and its sibling's subtree
+            rank += subtree_len(n, node - 1) + 1;
+        }
+        node >>= 1;
+    }
+    rank
+}
+
+/// Hints that the element at `index` will be read soon. The index may be out of bounds.
+#[inline(always)]
+fn prefetch<T>(data: &[T], index: usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        // SAFETY: prefetching is only a hint and never faults, and the pointer is not dereferenced.
+        unsafe { _mm_prefetch::<_MM_HINT_T0>(data.as_ptr().wrapping_add(index).cast()) }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = (data, index);
+}
+
+/// The first node in order for which `less` is false, or 0 if there is none.
+fn lower_bound<T>(data: &[T], mut less: impl FnMut(&T) -> bool) -> usize {
+    let n = data.len();
+    let mut k = 1;
+    while k <= n {
+        prefetch(data, (k << PREFETCH_LEVELS) - 1);
+        k = 2 * k + usize::from(less(&data[k - 1]));
+    }
+    // the path ends with the right turns taken after the last left turn, which was at the answer
+    k >> (k.trailing_ones() + 1)
+}
+
+/// The node holding `key`, if any.
+fn find<T: Borrow<Q>, Q: ?Sized + Ord>(data: &[T], key: &Q) -> Option<usize> {
+    let k = lower_bound(data, |x| x.borrow() < key);
+    (k != 0 && data[k - 1].borrow() == key).then_some(k)
+}
+
+/// The nodes whose elements lie within `range`.
+fn range<T, Q, R>(data: &[T], range: &R) -> Nodes
+where
+    T: Borrow<Q>,
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    search::check_bounds(range, Ord::cmp);
+    let n = data.len();
+    let start = match range.start_bound() {
+        Bound::Included(s) => lower_bound(data, |x| x.borrow() < s),
+        Bound::Excluded(s) => lower_bound(data, |x| x.borrow() <= s),
+        Bound::Unbounded => first(n),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(e) => lower_bound(data, |x| x.borrow() <= e),
+        Bound::Excluded(e) => lower_bound(data, |x| x.borrow() < e),
+        Bound::Unbounded => 0,
+    };
+    Nodes {
+        n,
+        front: start,
+        back: if end == 0 { last(n) } else { prev(n, end) },
+        len: rank(n, end) - rank(n, start),
+    }
+}
+
+/// Lays out a sorted vector in Eytzinger order.
+fn from_sorted<T>(sorted: Vec<T>) -> Vec<T> {
+    let n = sorted.len();
+    let mut slots: Vec<Option<T>> = (0..n).map(|_| None).collect();
+    for (k, x) in Nodes::all(n).zip(sorted) {
+        slots[k - 1] = Some(x);
+    }
+    slots.into_iter().map(Option::unwrap).collect()
+}
+
+/// Puts a vector in Eytzinger order back in sorted order.
+fn into_sorted<T>(data: Vec<T>) -> Vec<T> {
+    let n = data.len();
+    let mut slots: Vec<Option<T>> = data.into_iter().map(Some).collect();
+    Nodes::all(n)
+        .map(|k| slots[k - 1].take().unwrap())
+        .collect()
+}
+
+/// The nodes between `front` and `back` in order.
+#[derive(Debug, Clone)]
+struct Nodes {
+    n: usize,
+    front: usize,
+    back: usize,
+    len: usize,
+}
+
+impl Nodes {
+    fn all(n: usize) -> Self {
+        Self {
+            n,
+            front: first(n),
+            back: last(n),
+            len: n,
+        }
+    }
+}
+
+impl Iterator for Nodes {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let k = self.front;
+        self.front = next(self.n, k);
+        self.len -= 1;
+        Some(k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl DoubleEndedIterator for Nodes {
+    fn next_back(&mut self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let k = self.back;
+        self.back = prev(self.n, k);
+        self.len -= 1;
+        Some(k)
+    }
+}
+
+impl ExactSizeIterator for Nodes {}
+impl FusedIterator for Nodes {}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::eytzinger::*;
+
+    #[test]
+    pub fn nodes_in_order() {
+        for n in 0..70 {
+            let sorted: Vec<usize> = (0..n).collect();
+            let data = from_sorted(sorted.clone());
+            let forward: Vec<usize> = Nodes::all(n).map(|k| data[k - 1]).collect();
+            let backward: Vec<usize> = Nodes::all(n).rev().map(|k| data[k - 1]).collect();
+            assert_eq!(forward, sorted);
+            assert_eq!(backward, sorted.iter().rev().cloned().collect::<Vec<_>>());
+
+            for k in 1..=n {
+                assert_eq!(rank(n, k), data[k - 1]);
+            }
+            assert_eq!(into_sorted(data), sorted);
+        }
+    }
+
+    #[test]
+    pub fn lower_bound_matches_partition_point() {
+        for n in 0..70 {
+            let sorted: Vec<usize> = (0..n).map(|x| x * 2).collect();
+            let data = from_sorted(sorted.clone());
+            for key in 0..=(n * 2 + 1) {
+                let k = lower_bound(&data, |x| *x < key);
+                assert_eq!(rank(n, k), sorted.partition_point(|x| *x < key));
+                assert_eq!(find(&data, &key).is_some(), key % 2 == 0 && key < n * 2);
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::borrow::Borrow;
+use std::iter::FusedIterator;
+use std::ops::RangeBounds;
+
+use cc_traits::{
+    covariant_item_ref, covariant_key_ref, simple_collection_ref, simple_keyed_ref, Collection,
+    CollectionRef, Get, GetKeyValue, Keyed, KeyedRef, Len, MapIter, SimpleCollectionRef,
+    SimpleKeyedRef,
+};
+
+use crate::compare::Natural;
+use crate::eytzinger::{find, from_sorted, into_sorted, Nodes};
+use crate::map::BinaryMap;
+
+/// A frozen map with its keys and values stored in Eytzinger order. Build it from a [`BinaryMap`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct EytzingerMap<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+}
+
+impl<K, V> EytzingerMap<K, V> {
+    /// Iterates over the entries in order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            keys: &self.keys,
+            values: &self.values,
+            nodes: Nodes::all(self.keys.len()),
+        }
+    }
+
+    /// Iterates in order over the entries with keys within `range`.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        Iter {
+            keys: &self.keys,
+            values: &self.values,
+            nodes: super::range(&self.keys, &range),
+        }
+    }
+}
+
+impl<K, V> From<BinaryMap<K, V>> for EytzingerMap<K, V> {
+    fn from(map: BinaryMap<K, V>) -> Self {
+        let (keys, values) = map.into_parts();
+        Self {
+            keys: from_sorted(keys),
+            values: from_sorted(values),
+        }
+    }
+}
+
+impl<K, V> From<EytzingerMap<K, V>> for BinaryMap<K, V> {
+    fn from(map: EytzingerMap<K, V>) -> Self {
+        let keys = into_sorted(map.keys);
+        let values = into_sorted(map.values);
+        BinaryMap::from_sorted_parts_unchecked(keys, values, Natural)
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for EytzingerMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        BinaryMap::from_iter(iter).into()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a EytzingerMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> Collection for EytzingerMap<K, V> {
+    type Item = V;
+}
+
+impl<K, V> CollectionRef for EytzingerMap<K, V> {
+    type ItemRef<'a>
+        = &'a V
+    where
+        Self: 'a;
+
+    covariant_item_ref!();
+}
+
+impl<K, V> SimpleCollectionRef for EytzingerMap<K, V> {
+    simple_collection_ref!();
+}
+
+impl<K, V> Keyed for EytzingerMap<K, V> {
+    type Key = K;
+}
+
+impl<K, V> KeyedRef for EytzingerMap<K, V> {
+    type KeyRef<'a>
+        = &'a K
+    where
+        Self: 'a;
+
+    covariant_key_ref!();
+}
+
+impl<K, V> SimpleKeyedRef for EytzingerMap<K, V> {
+    simple_keyed_ref!();
+}
+
+impl<K, V> Len for EytzingerMap<K, V> {
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V> Get<&'a Q> for EytzingerMap<K, V> {
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let k = find(&self.keys, key)?;
+        Some(&self.values[k - 1])
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, K: Borrow<Q>, V> GetKeyValue<&'a Q> for EytzingerMap<K, V> {
+    fn get_key_value(&self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemRef<'_>)> {
+        let k = find(&self.keys, key)?;
+        Some((&self.keys[k - 1], &self.values[k - 1]))
+    }
+}
+
+impl<K, V> MapIter for EytzingerMap<K, V> {
+    type Iter<'a>
+        = Iter<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+/// In-order iterator over an [`EytzingerMap`], returned by `iter` and `range`.
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    keys: &'a [K],
+    values: &'a [V],
+    nodes: Nodes,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys,
+            values: self.values,
+            nodes: self.nodes.clone(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let k = self.nodes.next()?;
+        Some((&self.keys[k - 1], &self.values[k - 1]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let k = self.nodes.next_back()?;
+        Some((&self.keys[k - 1], &self.values[k - 1]))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::eytzinger::map::*;
+
+    #[test]
+    pub fn get() {
+        for n in 0..50u32 {
+            let map = EytzingerMap::from_iter((0..n).map(|x| (x * 2, x)));
+            assert_eq!(map.len(), n as usize);
+            for key in 0..(n * 2 + 2) {
+                let expected = (key % 2 == 0 && key < n * 2).then_some(key / 2);
+                assert_eq!(map.get(&key).copied(), expected);
+                assert_eq!(
+                    map.get_key_value(&key).map(|(k, _)| *k),
+                    expected.map(|_| key)
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn iter_and_range() {
+        let map = EytzingerMap::from_iter([(3, 'c'), (1, 'a'), (4, 'd'), (2, 'b'), (5, 'e')]);
+        assert_eq!(
+            map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            vec![(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd'), (5, 'e')]
+        );
+        assert_eq!(
+            map.range(2..=4).rev().map(|(_, v)| *v).collect::<Vec<_>>(),
+            vec!['d', 'c', 'b']
+        );
+        assert_eq!(map.range(6..).len(), 0);
+    }
+
+    #[test]
+    pub fn round_trip() {
+        let binary = BinaryMap::from_iter([("b".to_string(), 2), ("a".to_string(), 1)]);
+        let map = EytzingerMap::from(binary.clone());
+        assert_eq!(map.get("b"), Some(&2));
+        assert_eq!(BinaryMap::from(map), binary);
+    }
+}
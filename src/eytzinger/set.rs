@@ -0,0 +1,215 @@
+use std::borrow::Borrow;
+use std::iter::FusedIterator;
+use std::ops::RangeBounds;
+
+use cc_traits::{
+    covariant_item_ref, simple_collection_ref, Collection, CollectionRef, Get, Len,
+    SimpleCollectionRef,
+};
+
+use crate::compare::Natural;
+use crate::eytzinger::{find, from_sorted, into_sorted, Nodes};
+use crate::set::BinarySet;
+
+/// A frozen set stored in Eytzinger order. Build it from a [`BinarySet`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct EytzingerSet<T>(Vec<T>);
+
+impl<T> EytzingerSet<T> {
+    /// Iterates over the elements in order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            data: &self.0,
+            nodes: Nodes::all(self.0.len()),
+        }
+    }
+
+    /// Iterates in order over the elements within `range`.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        Iter {
+            data: &self.0,
+            nodes: super::range(&self.0, &range),
+        }
+    }
+}
+
+impl<T> From<BinarySet<T>> for EytzingerSet<T> {
+    fn from(set: BinarySet<T>) -> Self {
+        Self(from_sorted(set.into()))
+    }
+}
+
+impl<T> From<EytzingerSet<T>> for BinarySet<T> {
+    fn from(set: EytzingerSet<T>) -> Self {
+        BinarySet::from_sorted_vec_unchecked(into_sorted(set.0), Natural)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for EytzingerSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        BinarySet::from_iter(iter).into()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a EytzingerSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Collection for EytzingerSet<T> {
+    type Item = T;
+}
+
+impl<T> CollectionRef for EytzingerSet<T> {
+    type ItemRef<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    covariant_item_ref!();
+}
+
+impl<T> SimpleCollectionRef for EytzingerSet<T> {
+    simple_collection_ref!();
+}
+
+impl<T> Len for EytzingerSet<T> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<'a, Q: ?Sized + Ord, T: Borrow<Q>> Get<&'a Q> for EytzingerSet<T> {
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let k = find(&self.0, key)?;
+        Some(&self.0[k - 1])
+    }
+}
+
+impl<T> cc_traits::Iter for EytzingerSet<T> {
+    type Iter<'a>
+        = Iter<'a, T>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+/// In-order iterator over an [`EytzingerSet`], returned by `iter` and `range`.
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    data: &'a [T],
+    nodes: Nodes,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data,
+            nodes: self.nodes.clone(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let k = self.nodes.next()?;
+        Some(&self.data[k - 1])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let k = self.nodes.next_back()?;
+        Some(&self.data[k - 1])
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::eytzinger::set::*;
+    use std::collections::BTreeSet;
+    use std::ops::Bound;
+
+    #[test]
+    pub fn get() {
+        for n in 0..50 {
+            let set = EytzingerSet::from_iter((0..n).map(|x| x * 2));
+            assert_eq!(set.len(), n);
+            for x in 0..(n * 2 + 2) {
+                let expected = (x % 2 == 0 && x < n * 2).then_some(&x);
+                assert_eq!(set.get(&x), expected);
+            }
+        }
+    }
+
+    #[test]
+    pub fn iter() {
+        let set = EytzingerSet::from_iter([4, 2, 5, 1, 3, 2]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4, &5]);
+        assert_eq!(
+            set.iter().rev().collect::<Vec<_>>(),
+            vec![&5, &4, &3, &2, &1]
+        );
+
+        let mut iter = set.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.len(), 3);
+    }
+
+    #[test]
+    pub fn range_matches_btree_set() {
+        let values = [1, 3, 4, 7, 8, 9, 12, 15, 20, 21];
+        let set = EytzingerSet::from_iter(values);
+        let btree = BTreeSet::from_iter(values);
+
+        for a in 0..23 {
+            for b in a..23 {
+                for bounds in [
+                    (Bound::Included(a), Bound::Included(b)),
+                    (Bound::Included(a), Bound::Excluded(b)),
+                    (Bound::Excluded(a), Bound::Included(b)),
+                    (Bound::Unbounded, Bound::Included(b)),
+                    (Bound::Included(a), Bound::Unbounded),
+                ] {
+                    let expected: Vec<_> = btree.range(bounds).collect();
+                    assert_eq!(set.range(bounds).collect::<Vec<_>>(), expected);
+                    assert_eq!(set.range(bounds).len(), expected.len());
+                    assert_eq!(
+                        set.range(bounds).rev().collect::<Vec<_>>(),
+                        expected.into_iter().rev().collect::<Vec<_>>()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn round_trip() {
+        let binary = BinarySet::from_iter(["d", "a", "c", "b", "e"]);
+        let set = EytzingerSet::from(binary.clone());
+        assert_eq!(set.get("c"), Some(&"c"));
+        assert_eq!(BinarySet::from(set), binary);
+    }
+}
@@ -1,2 +1,29 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+mod algebra;
+pub mod array;
+mod buffer;
+#[cfg(feature = "std")]
+pub mod chunked;
+pub mod comparable;
+pub mod compare;
+#[cfg(all(test, feature = "std"))]
+mod conformance;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod eytzinger;
+#[cfg(feature = "std")]
 pub mod map;
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(feature = "std")]
+pub mod pair_map;
+mod search;
+#[cfg(feature = "std")]
 pub mod set;
+#[cfg(feature = "std")]
+pub mod static_btree;
+#[cfg(feature = "std")]
+pub mod storage;
+#[cfg(test)]
+mod test_support;
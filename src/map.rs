@@ -1,56 +1,480 @@
+use std::cmp::Ordering;
+use std::fmt;
 use std::ops::{IndexMut, RangeBounds};
 
 use cc_traits::{
-    covariant_item_mut, covariant_item_ref, covariant_key_ref, Capacity, Clear, Collection,
-    CollectionMut, CollectionRef, Get, GetKeyValue, GetMut, Iter, Keyed, KeyedRef, Len, MapInsert,
-    Remove, Reserve, WithCapacity,
+    covariant_item_mut, covariant_item_ref, covariant_key_ref, simple_collection_mut,
+    simple_collection_ref, simple_keyed_ref, Capacity, Clear, Collection, CollectionMut,
+    CollectionRef, Get, GetKeyValue, GetKeyValueMut, GetMut, Keyed, KeyedRef, Len, MapInsert,
+    MapIter, MapIterMut, Remove, Reserve, SimpleCollectionMut, SimpleCollectionRef, SimpleKeyedRef,
+    WithCapacity,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
-pub struct BinaryMap<K, V> {
-    keys: Vec<K>,
-    values: Vec<V>,
+use crate::buffer::Retain;
+use crate::comparable::Comparable;
+use crate::compare::{Compare, Natural};
+use crate::error::{DuplicateKeyError, FromSortedError};
+use crate::storage::{Buffer, SmallStore, SortedStorage, VecStore};
+use crate::{merge, search};
+
+mod entry;
+mod iter;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use iter::{Drain, ExtractIf, IntoIter, Iter, IterMut, Range, RangeMut, ValuesMut};
+
+/// A map stored as parallel sorted buffers of keys and values, ordered by the comparator `C`.
+/// The storage `S` picks the buffers, which are `Vec`s by default.
+///
+/// Lookups take a key by reference, as any `&Q` that `C` compares with `K`. There is no
+/// by-value `Get<K>` or `GetKeyValue<K>`, since either would overlap with the `&Q` impl when `K`
+/// is itself a reference, so code that passed keys by value must pass `&key` instead.
+#[derive(Debug, Clone, Default)]
+pub struct BinaryMapBy<K, V, C, S: SortedStorage = VecStore> {
+    keys: S::Buffer<K>,
+    values: S::Buffer<V>,
+    compare: C,
 }
 
-impl<K: Ord, V> BinaryMap<K, V> {
-    pub fn range(&self, range: impl RangeBounds<K>) -> impl Iterator<Item = (&K, &V)> {
-        let start_inclusive: usize = match range.start_bound() {
-            std::ops::Bound::Included(k) => match self.keys.binary_search(k) {
-                Ok(i) => i,
-                Err(i) => i,
-            },
-            std::ops::Bound::Excluded(k) => match self.keys.binary_search(k) {
-                Ok(i) => i + 1, //do not include this key
-                Err(i) => i,
-            },
-            std::ops::Bound::Unbounded => 0,
-        };
+/// A map stored as parallel sorted `Vec`s of keys and values, in the natural order of its keys.
+pub type BinaryMap<K, V> = BinaryMapBy<K, V, Natural, VecStore>;
 
-        let (end, exclusive): (usize, bool) = match range.end_bound() {
-            std::ops::Bound::Included(k) => match self.keys.binary_search(k) {
-                Ok(i) => (i, false),
-                Err(i) => (i, true),
-            },
-            std::ops::Bound::Excluded(k) => match self.keys.binary_search(k) {
-                Ok(i) => (i, true),
-                Err(i) => (i, true),
-            },
-            std::ops::Bound::Unbounded => (self.len(), true),
-        };
+/// A map that keeps up to `N` entries inline, in the natural order of its keys.
+pub type SmallBinaryMap<K, V, const N: usize> = BinaryMapBy<K, V, Natural, SmallStore<N>>;
+
+/// What [`BinaryMapBy::from_iter_with`] does with entries whose keys compare equal.
+/// The first such key is kept in every case.
+pub enum DuplicatePolicy<V> {
+    /// Keeps the first value.
+    FirstWins,
+    /// Keeps the last value, like `BTreeMap::from_iter` and `FromIterator`.
+    LastWins,
+    /// Fails with the second key.
+    Error,
+    /// Combines the value so far with the next one.
+    Merge(fn(V, V) -> V),
+}
+
+impl<V> DuplicatePolicy<V> {
+    /// Sorts `entries` by key and moves them into a new buffer, resolving equal keys by the
+    /// policy. The sort is stable, so entries with equal keys are resolved in the order they
+    /// came in.
+    pub(crate) fn sort_and_resolve<K, B: Buffer<(K, V)>>(
+        self,
+        mut entries: B,
+        compare: impl Fn(&K, &K) -> Ordering,
+    ) -> Result<B, DuplicateKeyError<K>> {
+        entries.sort_by(|a, b| compare(&a.0, &b.0));
+
+        let mut resolved = B::with_capacity(entries.len());
+        for (key, value) in entries {
+            match resolved.last_mut() {
+                Some(last) if compare(&last.0, &key).is_eq() => match self {
+                    DuplicatePolicy::FirstWins => {}
+                    DuplicatePolicy::LastWins => last.1 = value,
+                    DuplicatePolicy::Error => return Err(DuplicateKeyError(key)),
+                    DuplicatePolicy::Merge(merge) => {
+                        let (key, so_far) = resolved.pop().unwrap();
+                        resolved.push((key, merge(so_far, value)));
+                    }
+                },
+                _ => resolved.push((key, value)),
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+// by hand, as a derive would require `V: Clone` and `V: Debug` for a function pointer
+impl<V> Clone for DuplicatePolicy<V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for DuplicatePolicy<V> {}
+
+impl<V> fmt::Debug for DuplicatePolicy<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FirstWins => f.write_str("FirstWins"),
+            Self::LastWins => f.write_str("LastWins"),
+            Self::Error => f.write_str("Error"),
+            Self::Merge(merge) => f.debug_tuple("Merge").field(merge).finish(),
+        }
+    }
+}
+
+/// Borrows the keys and values of a map while both are being changed.
+/// If it is dropped with their lengths out of step, which only happens when a panic unwinds
+/// between the two updates, both are cleared so that no key is left paired with the wrong value.
+struct Lockstep<'a, K, V, S: SortedStorage> {
+    keys: &'a mut S::Buffer<K>,
+    values: &'a mut S::Buffer<V>,
+}
+
+impl<K, V, S: SortedStorage> Drop for Lockstep<'_, K, V, S> {
+    fn drop(&mut self) {
+        if self.keys.len() != self.values.len() {
+            self.keys.truncate(0);
+            self.values.truncate(0);
+        }
+    }
+}
+
+impl<K, V, C> BinaryMapBy<K, V, C> {
+    pub const fn with_comparator(compare: C) -> Self {
+        Self {
+            keys: Vec::new(),
+            values: Vec::new(),
+            compare,
+        }
+    }
+
+    /// Wraps keys that are already sorted and deduplicated by `compare`, with their values.
+    pub(crate) fn from_sorted_parts_unchecked(keys: Vec<K>, values: Vec<V>, compare: C) -> Self {
+        debug_assert_eq!(keys.len(), values.len());
+        Self {
+            keys,
+            values,
+            compare,
+        }
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<K>, Vec<V>) {
+        (self.keys, self.values)
+    }
+
+    /// Removes the entries whose keys lie within `range` and iterates over them in order.
+    pub fn drain_range<Q, R>(&mut self, range: R) -> Drain<'_, K, V>
+    where
+        C: Compare<Q, K> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = self.find_range(&range);
+        Drain {
+            inner: self.keys.drain(range.clone()).zip(self.values.drain(range)),
+        }
+    }
+
+    /// Removes every entry and iterates over them in order.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain {
+            inner: self.keys.drain(..).zip(self.values.drain(..)),
+        }
+    }
+
+    /// Removes the entries for which `extract` returns true as the iterator reaches them.
+    /// Entries it does not reach are kept.
+    pub fn extract_if<F: FnMut(&K, &mut V) -> bool>(
+        &mut self,
+        extract: F,
+    ) -> ExtractIf<'_, K, V, F> {
+        ExtractIf::new(&mut self.keys, &mut self.values, extract)
+    }
+}
+
+impl<K, V, C, S: SortedStorage> BinaryMapBy<K, V, C, S> {
+    pub const fn comparator(&self) -> &C {
+        &self.compare
+    }
+
+    /// Moves the entries into the storage `S2`.
+    pub fn into_storage<S2: SortedStorage>(self) -> BinaryMapBy<K, V, C, S2> {
+        let (keys, values): (Vec<K>, Vec<V>) = (self.keys.into(), self.values.into());
+        BinaryMapBy {
+            keys: keys.into(),
+            values: values.into(),
+            compare: self.compare,
+        }
+    }
+
+    /// The buffer the keys are stored in.
+    pub fn key_buffer(&self) -> &S::Buffer<K> {
+        &self.keys
+    }
+
+    fn lockstep(&mut self) -> Lockstep<'_, K, V, S> {
+        Lockstep {
+            keys: &mut self.keys,
+            values: &mut self.values,
+        }
+    }
+
+    /// Inserts an entry at `index`. Nothing is dropped until both vectors agree again.
+    fn insert_index(&mut self, index: usize, key: K, value: V) {
+        let pair = self.lockstep();
+        pair.keys.insert(index, key);
+        pair.values.insert(index, value);
+    }
+
+    /// Removes the entry at `index`, which must be in bounds. Nothing is dropped here,
+    /// so a panicking `Drop` impl cannot run between the two removals.
+    fn take_index(&mut self, index: usize) -> (K, V) {
+        let pair = self.lockstep();
+        (pair.keys.remove(index), pair.values.remove(index))
+    }
+
+    fn find<Q: ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        C: Compare<Q, K>,
+    {
+        search::find(&self.keys, key, |q, k| self.compare.compare(q, k))
+    }
+
+    fn find_range<Q, R>(&self, range: &R) -> std::ops::Range<usize>
+    where
+        C: Compare<Q, K> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(range, |a, b| self.compare.compare(a, b));
+        search::range(&self.keys, range, |q, k| self.compare.compare(q, k))
+    }
+}
+
+impl<K, V, C: Compare<K>> BinaryMapBy<K, V, C> {
+    /// Collects `iter`, keeping the last value for each key.
+    pub fn from_iter_with_comparator<I: IntoIterator<Item = (K, V)>>(iter: I, compare: C) -> Self {
+        Self::collect_with_comparator(iter, compare)
+    }
+
+    /// Wraps `keys` and `values` after checking that there are as many of each,
+    /// and in linear time that the keys are sorted and deduplicated by `compare`.
+    pub fn from_sorted_parts_with_comparator(
+        keys: Vec<K>,
+        values: Vec<V>,
+        compare: C,
+    ) -> Result<Self, FromSortedError> {
+        if keys.len() != values.len() {
+            return Err(FromSortedError::LengthMismatch {
+                keys: keys.len(),
+                values: values.len(),
+            });
+        }
+        search::check_sorted(&keys, |a, b| compare.compare(a, b))?;
+        Ok(Self {
+            keys,
+            values,
+            compare,
+        })
+    }
+
+    /// Collects `iter` after checking in linear time that its keys are sorted and deduplicated
+    /// by `compare`. The keys and values go straight into their own `Vec`s.
+    pub fn from_sorted_iter_with_comparator<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        compare: C,
+    ) -> Result<Self, FromSortedError> {
+        let iter = iter.into_iter();
+        let mut keys: Vec<K> = Vec::with_capacity(iter.size_hint().0);
+        let mut values = Vec::with_capacity(iter.size_hint().0);
+        for (index, (key, value)) in iter.enumerate() {
+            if let Some(last) = keys.last() {
+                search::check_next(last, &key, index, |a, b| compare.compare(a, b))?;
+            }
+            keys.push(key);
+            values.push(value);
+        }
+        Ok(Self {
+            keys,
+            values,
+            compare,
+        })
+    }
+}
 
-        if exclusive {
-            self.keys[start_inclusive..end]
+impl<K, V, C: Compare<K>, S: SortedStorage> BinaryMapBy<K, V, C, S> {
+    fn collect_with_comparator<I: IntoIterator<Item = (K, V)>>(iter: I, compare: C) -> Self {
+        match Self::from_iter_with_policy(iter, DuplicatePolicy::LastWins, compare) {
+            Ok(map) => map,
+            Err(_) => unreachable!("only DuplicatePolicy::Error fails"),
+        }
+    }
+
+    /// Sorts and resolves the entries as pairs in a buffer of the map's own storage, then moves
+    /// them into the key and value buffers. Sorting pairs keeps equal keys in the order they
+    /// came in, at the cost of holding the entries twice while they move.
+    fn from_iter_with_policy<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        policy: DuplicatePolicy<V>,
+        compare: C,
+    ) -> Result<Self, DuplicateKeyError<K>> {
+        let entries = S::Buffer::<(K, V)>::from_iter(iter);
+        let entries = policy.sort_and_resolve(entries, |a, b| compare.compare(a, b))?;
+
+        let mut keys = S::Buffer::<K>::with_capacity(entries.len());
+        let mut values = S::Buffer::<V>::with_capacity(entries.len());
+        for (key, value) in entries {
+            keys.push(key);
+            values.push(value);
+        }
+
+        Ok(Self {
+            keys,
+            values,
+            compare,
+        })
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        C: Compare<Q, K> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = self.find_range(&range);
+        Range {
+            inner: self.keys[range.clone()]
                 .iter()
-                .zip(self.values[start_inclusive..end].iter())
-        } else {
-            self.keys[start_inclusive..=end]
+                .zip(self.values[range].iter()),
+        }
+    }
+
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        C: Compare<Q, K> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = self.find_range(&range);
+        RangeMut {
+            inner: self.keys[range.clone()]
+                .iter()
+                .zip(self.values[range].iter_mut()),
+        }
+    }
+
+    /// Splits off the entries with keys greater than or equal to `key` into a new map.
+    pub fn split_off<Q: ?Sized>(&mut self, key: &Q) -> Self
+    where
+        C: Compare<Q, K> + Clone,
+    {
+        let index = search::lower_bound(&self.keys, key, |q, k| self.compare.compare(q, k));
+        let compare = self.compare.clone();
+        let pair = self.lockstep();
+        Self {
+            keys: pair.keys.split_off(index),
+            values: pair.values.split_off(index),
+            compare,
+        }
+    }
+
+    /// Moves every entry of `other` into `self` with one merge, leaving `other` empty.
+    /// Entries of `other` replace entries of `self` with equal keys, as in `BTreeMap::append`.
+    pub fn append(&mut self, other: &mut Self) {
+        let len = self.keys.len() + other.keys.len();
+        let pair = Lockstep::<K, V, S> {
+            keys: &mut self.keys,
+            values: &mut self.values,
+        };
+        let keys = std::mem::replace(pair.keys, S::Buffer::with_capacity(len));
+        let values = std::mem::replace(pair.values, S::Buffer::with_capacity(len));
+        let other_keys = std::mem::take(&mut other.keys);
+        let other_values = std::mem::take(&mut other.values);
+        merge::union_entries(
+            keys.into_iter().zip(values),
+            other_keys.into_iter().zip(other_values),
+            |a, b| self.compare.compare(a, b),
+            |key, value| {
+                pair.keys.push(key);
+                pair.values.push(value);
+            },
+        );
+    }
+}
+
+impl<K, V, C: Compare<K> + Default> BinaryMapBy<K, V, C> {
+    /// Collects `iter`, resolving entries with equal keys by `policy`.
+    /// Only [`DuplicatePolicy::Error`] can fail.
+    pub fn from_iter_with<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        policy: DuplicatePolicy<V>,
+    ) -> Result<Self, DuplicateKeyError<K>> {
+        Self::from_iter_with_policy(iter, policy, C::default())
+    }
+
+    /// Wraps `keys` and `values` after checking that there are as many of each,
+    /// and in linear time that the keys are sorted and deduplicated.
+    pub fn from_sorted_parts(keys: Vec<K>, values: Vec<V>) -> Result<Self, FromSortedError> {
+        Self::from_sorted_parts_with_comparator(keys, values, C::default())
+    }
+
+    /// Splits `vec` into keys and values after checking in linear time that the keys are
+    /// sorted and deduplicated.
+    pub fn from_sorted_vec(vec: Vec<(K, V)>) -> Result<Self, FromSortedError> {
+        Self::from_sorted_iter(vec)
+    }
+
+    /// Collects `iter` after checking in linear time that its keys are sorted and deduplicated.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+    ) -> Result<Self, FromSortedError> {
+        Self::from_sorted_iter_with_comparator(iter, C::default())
+    }
+
+    /// Wraps `keys` and `values` without checking them.
+    ///
+    /// # Safety
+    ///
+    /// `keys` must be sorted and deduplicated by the comparator, and as long as `values`.
+    /// Every method of the map relies on this, and may otherwise return wrong results or panic.
+    pub unsafe fn from_sorted_unchecked(keys: Vec<K>, values: Vec<V>) -> Self {
+        Self::from_sorted_parts_unchecked(keys, values, C::default())
+    }
+}
+
+/// Lookups with [`Comparable`] queries, which follow the natural order of the keys, so these
+/// are only available on maps in that order. With another comparator, use `get` and the other
+/// lookups, which search with the comparator:
+///
+/// ```compile_fail
+/// use binary_tree_collections::compare::{Natural, Reverse};
+/// use binary_tree_collections::map::BinaryMapBy;
+///
+/// let map = BinaryMapBy::from_iter_with_comparator([(1, 'a'), (2, 'b')], Reverse(Natural));
+/// map.get_by(&1);
+/// ```
+impl<K, V, S: SortedStorage> BinaryMapBy<K, V, Natural, S> {
+    /// Like `get`, but accepts any query that is [`Comparable`] with the keys.
+    /// The query must agree with the order of the map.
+    pub fn get_by<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&V> {
+        let index = search::find(&self.keys, key, |q, k| q.compare(k)).ok()?;
+        self.values.get(index)
+    }
+
+    pub fn get_mut_by<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> Option<&mut V> {
+        let index = search::find(&self.keys, key, |q, k| q.compare(k)).ok()?;
+        self.values.get_mut(index)
+    }
+
+    pub fn get_key_value_by<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(&K, &V)> {
+        let index = search::find(&self.keys, key, |q, k| q.compare(k)).ok()?;
+        Some((&self.keys[index], &self.values[index]))
+    }
+
+    /// Like `range`, but accepts any bounds that are [`Comparable`] with the keys and with each other.
+    pub fn range_by<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        Q: ?Sized + Comparable<K> + Comparable<Q>,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(&range, Comparable::<Q>::compare);
+        let range = search::range(&self.keys, &range, |q, k| q.compare(k));
+        Range {
+            inner: self.keys[range.clone()]
                 .iter()
-                .zip(self.values[start_inclusive..=end].iter())
+                .zip(self.values[range].iter()),
         }
     }
+
+    pub fn remove_by<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> Option<V> {
+        let index = search::find(&self.keys, key, |q, k| q.compare(k)).ok()?;
+        Some(self.take_index(index).1)
+    }
 }
 
-impl<K, V> BinaryMap<K, V> {
+impl<K, V, C> BinaryMapBy<K, V, C> {
     pub const fn keys(&self) -> &Vec<K> {
         &self.keys
     }
@@ -69,11 +493,148 @@ impl<K, V> BinaryMap<K, V> {
 
 }
 
-// impl<K, V> Map<K, V> for BinaryMap<K, V>{
+impl<K, V, C, S: SortedStorage> BinaryMapBy<K, V, C, S> {
+    pub fn values_mut(&mut self) -> ValuesMut<'_, V> {
+        ValuesMut {
+            inner: self.values.iter_mut(),
+        }
+    }
+
+    /// Iterates over the entries in order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.keys.iter().zip(self.values.iter()),
+        }
+    }
+
+    /// Iterates over the entries in order, with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.keys.iter().zip(self.values.iter_mut()),
+        }
+    }
+
+    /// The position of `key` if it is present, or else where it would be inserted.
+    pub fn index_of<Q: ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        C: Compare<Q, K>,
+    {
+        self.find(key)
+    }
+
+    /// The number of keys less than `key`.
+    pub fn rank<Q: ?Sized>(&self, key: &Q) -> usize
+    where
+        C: Compare<Q, K>,
+    {
+        self.find(key).unwrap_or_else(|index| index)
+    }
+
+    /// The entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        Some((self.keys.get(index)?, &self.values[index]))
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        Some((self.keys.get(index)?, &mut self.values[index]))
+    }
+
+    /// Removes the entry at position `index`, shifting the ones after it down.
+    pub fn remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.keys.len() {
+            return None;
+        }
+        Some(self.take_index(index))
+    }
+
+    /// The entry with the greatest key less than or equal to `key`.
+    pub fn floor<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let index = search::upper_bound(&self.keys, key, |q, k| self.compare.compare(q, k));
+        self.get_index(index.checked_sub(1)?)
+    }
+
+    /// The entry with the least key greater than or equal to `key`.
+    pub fn ceiling<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let index = search::lower_bound(&self.keys, key, |q, k| self.compare.compare(q, k));
+        self.get_index(index)
+    }
+
+    /// The entry with the greatest key strictly less than `key`.
+    pub fn lower<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let index = search::lower_bound(&self.keys, key, |q, k| self.compare.compare(q, k));
+        self.get_index(index.checked_sub(1)?)
+    }
+
+    /// The entry with the least key strictly greater than `key`.
+    pub fn higher<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let index = search::upper_bound(&self.keys, key, |q, k| self.compare.compare(q, k));
+        self.get_index(index)
+    }
+
+    /// The entry whose key is nearest to `key` by `distance`, which must grow with distance
+    /// in the order of the map. Ties go to the smaller key.
+    pub fn nearest<Q: ?Sized, D: PartialOrd>(
+        &self,
+        key: &Q,
+        distance: impl Fn(&Q, &K) -> D,
+    ) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let compare = |q: &Q, k: &K| self.compare.compare(q, k);
+        self.get_index(search::nearest(&self.keys, key, compare, distance)?)
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.get_index(self.keys.len().checked_sub(1)?)
+    }
+
+    /// Removes the entry with the smallest key. This shifts every other entry down.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.remove_index(0)
+    }
 
-// }
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        Some((self.keys.pop()?, self.values.pop()?))
+    }
+
+    /// Keeps only the entries for which `keep` returns true, in one pass.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        let pair = self.lockstep();
+        // the values take the same steps as the keys, so both compact alike
+        let mut keys = Retain::new(&mut *pair.keys, Buffer::truncate);
+        let mut values = Retain::new(&mut *pair.values, Buffer::truncate);
+        while let (Some(key), Some(value)) =
+            (keys.unread().first(), values.unread_mut().first_mut())
+        {
+            if keep(key, value) {
+                keys.keep(1);
+                values.keep(1);
+            } else {
+                keys.skip(1);
+                values.skip(1);
+            }
+        }
+    }
+}
 
-impl<K, V> CollectionMut for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> CollectionMut for BinaryMapBy<K, V, C, S> {
     type ItemMut<'a> = &'a mut V
 	    where
 		    Self: 'a;
@@ -81,31 +642,29 @@ impl<K, V> CollectionMut for BinaryMap<K, V> {
     covariant_item_mut!();
 }
 
-impl<'a, K: Ord, V> GetMut<&'a K> for BinaryMap<K, V> {
-    fn get_mut(&mut self, key: &'a K) -> Option<Self::ItemMut<'_>> {
-        let index = self.keys.binary_search(key).ok()?;
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> GetMut<&'a Q>
+    for BinaryMapBy<K, V, C, S>
+{
+    fn get_mut(&mut self, key: &'a Q) -> Option<Self::ItemMut<'_>> {
+        let index = self.find(key).ok()?;
         self.values.get_mut(index)
     }
 }
 
-impl<K: Ord, V> Get<K> for BinaryMap<K, V> {
-    fn get(&self, key: K) -> Option<Self::ItemRef<'_>> {
-        self.get(&key)
-    }
-}
-
-impl<'a, K: Ord, V> Get<&'a K> for BinaryMap<K, V> {
-    fn get(&self, key: &'a K) -> Option<Self::ItemRef<'_>> {
-        let index = self.keys.binary_search(key).ok()?;
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> Get<&'a Q>
+    for BinaryMapBy<K, V, C, S>
+{
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let index = self.find(key).ok()?;
         self.values.get(index)
     }
 }
 
-impl<K: Ord, V> MapInsert<K> for BinaryMap<K, V> {
+impl<K, V, C: Compare<K>, S: SortedStorage> MapInsert<K> for BinaryMapBy<K, V, C, S> {
     type Output = Option<V>;
 
     fn insert(&mut self, key: K, mut value: Self::Item) -> Self::Output {
-        match self.keys.binary_search(&key) {
+        match self.find(&key) {
             Ok(index) => {
                 let position = self.values.index_mut(index);
                 std::mem::swap(&mut value, position);
@@ -113,25 +672,27 @@ impl<K: Ord, V> MapInsert<K> for BinaryMap<K, V> {
                 Some(value)
             }
             Err(index) => {
-                self.keys.insert(index, key);
-                self.values.insert(index, value);
+                self.insert_index(index, key, value);
                 None
             }
         }
     }
 }
 
-impl<'a, K: Ord, V> Remove<&'a K> for BinaryMap<K, V> {
-    fn remove(&mut self, key: &'a K) -> Option<Self::Item> {
-        let index = self.keys.binary_search(key).ok()?;
-        self.keys.remove(index);
-        Some(self.values.remove(index))
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> Remove<&'a Q>
+    for BinaryMapBy<K, V, C, S>
+{
+    fn remove(&mut self, key: &'a Q) -> Option<Self::Item> {
+        let index = self.find(key).ok()?;
+        Some(self.take_index(index).1)
     }
 }
 
-impl<'a, K: Ord, V> GetKeyValue<&'a K> for BinaryMap<K, V> {
-    fn get_key_value(&self, key: &'a K) -> Option<(Self::KeyRef<'_>, Self::ItemRef<'_>)> {
-        let index = self.keys.binary_search(key).ok()?;
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> GetKeyValue<&'a Q>
+    for BinaryMapBy<K, V, C, S>
+{
+    fn get_key_value(&self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemRef<'_>)> {
+        let index = self.find(key).ok()?;
 
         let k = self.keys.get(index)?;
         let v = self.values.get(index)?;
@@ -140,13 +701,16 @@ impl<'a, K: Ord, V> GetKeyValue<&'a K> for BinaryMap<K, V> {
     }
 }
 
-impl<K: Ord, V> GetKeyValue<K> for BinaryMap<K, V> {
-    fn get_key_value(&self, key: K) -> Option<(Self::KeyRef<'_>, Self::ItemRef<'_>)> {
-        self.get_key_value(&key)
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> GetKeyValueMut<&'a Q>
+    for BinaryMapBy<K, V, C, S>
+{
+    fn get_key_value_mut(&mut self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemMut<'_>)> {
+        let index = self.find(key).ok()?;
+        Some((&self.keys[index], &mut self.values[index]))
     }
 }
 
-impl<K, V> KeyedRef for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> KeyedRef for BinaryMapBy<K, V, C, S> {
     type KeyRef<'a> = &'a K
 	    where
 		    Self: 'a;
@@ -154,53 +718,121 @@ impl<K, V> KeyedRef for BinaryMap<K, V> {
     covariant_key_ref!();
 }
 
-impl<K, V> Keyed for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> Keyed for BinaryMapBy<K, V, C, S> {
     type Key = K;
 }
 
-impl<K, V> Clear for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> Clear for BinaryMapBy<K, V, C, S> {
     fn clear(&mut self) {
-        self.keys.clear();
-        self.values.clear();
+        let pair = self.lockstep();
+        pair.keys.truncate(0);
+        pair.values.truncate(0);
     }
 }
 
 impl<K, V> BinaryMap<K, V> {}
 
-impl<K: Ord, V> FromIterator<(K, V)> for BinaryMap<K, V> {
+impl<K: PartialEq, V: PartialEq, C, S: SortedStorage> PartialEq for BinaryMapBy<K, V, C, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.keys[..] == other.keys[..] && self.values[..] == other.values[..]
+    }
+}
+
+impl<K: Eq, V: Eq, C, S: SortedStorage> Eq for BinaryMapBy<K, V, C, S> {}
+
+impl<K: PartialOrd, V: PartialOrd, C, S: SortedStorage> PartialOrd for BinaryMapBy<K, V, C, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.keys[..].partial_cmp(&other.keys[..]) {
+            Some(Ordering::Equal) => self.values[..].partial_cmp(&other.values[..]),
+            ordering => ordering,
+        }
+    }
+}
+
+impl<K: Ord, V: Ord, C, S: SortedStorage> Ord for BinaryMapBy<K, V, C, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.keys[..]
+            .cmp(&other.keys[..])
+            .then_with(|| self.values[..].cmp(&other.values[..]))
+    }
+}
+
+impl<K, V, C: Compare<K> + Default, S: SortedStorage> FromIterator<(K, V)>
+    for BinaryMapBy<K, V, C, S>
+{
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
-        let mut vec: Vec<(K, V)> = Vec::from_iter(iter);
-        vec.sort_by(|a, b| a.0.cmp(&b.0));
-        vec.dedup_by(|a, b| a.0.eq(&b.0));
-        //todo don't allocate twice
-        let (keys, values) = vec.into_iter().unzip();
+        Self::collect_with_comparator(iter, C::default())
+    }
+}
 
-        Self { keys, values }
+impl<K, V, C: Compare<K>, S: SortedStorage> Extend<(K, V)> for BinaryMapBy<K, V, C, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+        // while the keys are inline, entries go in one at a time so that small maps never allocate
+        while self.keys.as_vec_mut().is_none() {
+            let Some((key, value)) = iter.next() else {
+                return;
+            };
+            self.insert(key, value);
+        }
+        let compare = &self.compare;
+        let values = &mut self.values;
+        let mut new_keys = Vec::new();
+        let mut new_values = Vec::new();
+        let indices = merge::place_batch(
+            &mut self.keys,
+            |k| k,
+            Vec::from_iter(iter),
+            |a, b| compare.compare(a, b),
+            |_, index, value| values[index] = value,
+            |key, value| {
+                new_keys.push(key);
+                new_values.push(value);
+            },
+        );
+
+        let pair = self.lockstep();
+        pair.keys
+            .with_vec(|keys| merge::insert_at(keys, &indices, new_keys));
+        pair.values
+            .with_vec(|values| merge::insert_at(values, &indices, new_values));
+    }
+}
+
+impl<'a, K: Copy, V: Copy, C: Compare<K>, S: SortedStorage> Extend<(&'a K, &'a V)>
+    for BinaryMapBy<K, V, C, S>
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(k, v)| (*k, *v)))
     }
 }
 
-impl<K, V> Reserve for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> Reserve for BinaryMapBy<K, V, C, S> {
     fn reserve(&mut self, additional: usize) {
         self.keys.reserve(additional);
         self.values.reserve(additional);
     }
 }
 
-impl<K, V> WithCapacity for BinaryMap<K, V> {
+impl<K, V, C: Default, S: SortedStorage> WithCapacity for BinaryMapBy<K, V, C, S> {
     fn with_capacity(capacity: usize) -> Self {
-        let keys = Vec::with_capacity(capacity);
-        let values = Vec::with_capacity(capacity);
-        Self { keys, values }
+        let keys = S::Buffer::with_capacity(capacity);
+        let values = S::Buffer::with_capacity(capacity);
+        Self {
+            keys,
+            values,
+            compare: C::default(),
+        }
     }
 }
 
-impl<K, V> Capacity for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> Capacity for BinaryMapBy<K, V, C, S> {
     fn capacity(&self) -> usize {
         self.keys.capacity()
     }
 }
 
-impl<K, V> Iter for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> cc_traits::Iter for BinaryMapBy<K, V, C, S> {
     type Iter<'a> = core::slice::Iter<'a, V>
 	    where
 		    Self: 'a;
@@ -210,7 +842,7 @@ impl<K, V> Iter for BinaryMap<K, V> {
     }
 }
 
-impl<K, V> CollectionRef for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> CollectionRef for BinaryMapBy<K, V, C, S> {
     type ItemRef<'a>= &'a V
 	    where
 		    Self: 'a ;
@@ -218,20 +850,84 @@ impl<K, V> CollectionRef for BinaryMap<K, V> {
     covariant_item_ref!();
 }
 
-impl<K, V> Collection for BinaryMap<K, V> {
+impl<K, V, C, S: SortedStorage> Collection for BinaryMapBy<K, V, C, S> {
     type Item = V;
 }
-impl<K, V> Len for BinaryMap<K, V> {
+
+impl<K, V, C, S: SortedStorage> SimpleCollectionRef for BinaryMapBy<K, V, C, S> {
+    simple_collection_ref!();
+}
+
+impl<K, V, C, S: SortedStorage> SimpleCollectionMut for BinaryMapBy<K, V, C, S> {
+    simple_collection_mut!();
+}
+
+impl<K, V, C, S: SortedStorage> SimpleKeyedRef for BinaryMapBy<K, V, C, S> {
+    simple_keyed_ref!();
+}
+
+impl<K, V, C, S: SortedStorage> MapIter for BinaryMapBy<K, V, C, S> {
+    type Iter<'a>
+        = Iter<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+impl<K, V, C, S: SortedStorage> MapIterMut for BinaryMapBy<K, V, C, S> {
+    type IterMut<'a>
+        = IterMut<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.iter_mut()
+    }
+}
+impl<K, V, C, S: SortedStorage> Len for BinaryMapBy<K, V, C, S> {
     fn len(&self) -> usize {
         self.keys.len()
     }
 }
 
+impl<K, V, C, S: SortedStorage> IntoIterator for BinaryMapBy<K, V, C, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.keys.into_iter().zip(self.values),
+        }
+    }
+}
+
+impl<'a, K, V, C, S: SortedStorage> IntoIterator for &'a BinaryMapBy<K, V, C, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, C, S: SortedStorage> IntoIterator for &'a mut BinaryMapBy<K, V, C, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::ops::Bound;
 
     use crate::map::*;
+    use crate::test_support::{assert_lockstep_after, Brittle};
 
     #[test]
     pub fn len() {
@@ -243,8 +939,8 @@ pub mod tests {
     pub fn get() {
         let set = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c')]);
 
-        assert_eq!(set.get(2), Some(&'b'));
-        assert_eq!(set.get(4), None);
+        assert_eq!(set.get(&2), Some(&'b'));
+        assert_eq!(set.get(&4), None);
     }
 
     #[test]
@@ -298,8 +994,8 @@ pub mod tests {
     pub fn get_key_value() {
         let set = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c')]);
 
-        assert_eq!(set.get_key_value(2), Some((&2, &'b')));
-        assert_eq!(set.get(4), None);
+        assert_eq!(set.get_key_value(&2), Some((&2, &'b')));
+        assert_eq!(set.get(&4), None);
     }
 
     #[test]
@@ -329,17 +1025,173 @@ pub mod tests {
     #[test]
     pub fn iter() {
         let set = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c')]);
-        let vec: Vec<char> = set.iter().cloned().collect();
-        assert_eq!(vec, vec!['a', 'b', 'c'])
+        let vec: Vec<char> = cc_traits::Iter::iter(&set).cloned().collect();
+        assert_eq!(vec, vec!['a', 'b', 'c']);
+
+        let vec: Vec<(i32, char)> = set.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(vec, vec![(1, 'a'), (2, 'b'), (3, 'c')])
+    }
+
+    #[test]
+    pub fn from_iter_last_wins() {
+        let entries = [(2, 'a'), (1, 'b'), (2, 'c'), (2, 'd')];
+        let map = BinaryMap::from_iter(entries);
+        let btree = std::collections::BTreeMap::from_iter(entries);
+        assert!(map.iter().eq(btree.iter()));
+    }
+
+    #[test]
+    pub fn from_iter_with_policy() {
+        use crate::error::DuplicateKeyError;
+
+        let entries = [(2, 1), (1, 10), (2, 2), (2, 3)];
+        let collect = |policy| BinaryMap::from_iter_with(entries, policy);
+        let first = collect(DuplicatePolicy::FirstWins).unwrap();
+        assert_eq!(first.values(), &vec![10, 1]);
+        let last = collect(DuplicatePolicy::LastWins).unwrap();
+        assert_eq!(last.values(), &vec![10, 3]);
+        let merged = collect(DuplicatePolicy::Merge(|a, b| a + b)).unwrap();
+        assert_eq!(merged.values(), &vec![10, 6]);
+        assert_eq!(collect(DuplicatePolicy::Error), Err(DuplicateKeyError(2)));
+        assert!(BinaryMap::from_iter_with([(1, 1), (2, 2)], DuplicatePolicy::Error).is_ok());
+    }
+
+    #[test]
+    pub fn duplicate_policy_needs_no_value_bounds() {
+        struct Opaque(String);
+
+        let policy: DuplicatePolicy<Opaque> = DuplicatePolicy::Merge(|a, b| Opaque(a.0 + &b.0));
+        let copy = policy;
+        assert_eq!(format!("{policy:?}").split('(').next(), Some("Merge"));
+        let error = DuplicatePolicy::<Opaque>::Error;
+        assert_eq!(format!("{error:?}"), "Error");
+
+        let entries = [(1, Opaque("a".into())), (1, Opaque("b".into()))];
+        let map = BinaryMap::from_iter_with(entries, copy).unwrap();
+        assert_eq!(map.values()[0].0, "ab");
+    }
+
+    #[test]
+    pub fn positional() {
+        let mut map = BinaryMap::from_iter([(10, 'a'), (20, 'b'), (30, 'c'), (40, 'd')]);
+        assert_eq!(map.index_of(&30), Ok(2));
+        assert_eq!(map.index_of(&5), Err(0));
+        assert_eq!(map.rank(&35), 3);
+        assert_eq!(map.get_index(1), Some((&20, &'b')));
+        assert_eq!(map.get_index(4), None);
+
+        *map.get_index_mut(1).unwrap().1 = 'x';
+        assert_eq!(map.remove_index(1), Some((20, 'x')));
+        assert_eq!(map.remove_index(3), None);
+        assert_eq!(map.first(), Some((&10, &'a')));
+        assert_eq!(map.last(), Some((&40, &'d')));
+        assert_eq!(map.pop_first(), Some((10, 'a')));
+        assert_eq!(map.pop_last(), Some((40, 'd')));
+        assert_eq!(map.pop_last(), Some((30, 'c')));
+        assert_eq!(map.pop_last(), None);
+        assert_eq!(map.last(), None);
+    }
+
+    #[test]
+    pub fn neighbours() {
+        let map = BinaryMap::from_iter([(100, "low"), (105, "mid"), (110, "high")]);
+        assert_eq!(map.floor(&104), Some((&100, &"low")));
+        assert_eq!(map.floor(&105), Some((&105, &"mid")));
+        assert_eq!(map.ceiling(&106), Some((&110, &"high")));
+        assert_eq!(map.ceiling(&111), None);
+        assert_eq!(map.lower(&105), Some((&100, &"low")));
+        assert_eq!(map.lower(&100), None);
+        assert_eq!(map.higher(&105), Some((&110, &"high")));
+        assert_eq!(map.higher(&99), Some((&100, &"low")));
+
+        let distance = |a: &u32, b: &u32| a.abs_diff(*b);
+        assert_eq!(map.nearest(&108, distance), Some((&110, &"high")));
+        assert_eq!(map.nearest(&0, distance), Some((&100, &"low")));
+    }
+
+    #[test]
+    pub fn from_sorted() {
+        use crate::error::FromSortedError;
+
+        let map = BinaryMap::from_sorted_parts(vec![1, 2], vec!['a', 'b']).unwrap();
+        assert_eq!(map.get(&2), Some(&'b'));
+        assert_eq!(
+            BinaryMap::from_sorted_parts(vec![1, 2], vec!['a']),
+            Err(FromSortedError::LengthMismatch { keys: 2, values: 1 })
+        );
+        assert_eq!(
+            BinaryMap::from_sorted_parts(vec![2, 1], vec!['a', 'b']),
+            Err(FromSortedError::Unsorted { index: 1 })
+        );
+
+        let map = BinaryMap::from_sorted_iter((0..5).map(|k| (k, k * 10))).unwrap();
+        assert_eq!(map.keys(), &vec![0, 1, 2, 3, 4]);
+        assert_eq!(map.values(), &vec![0, 10, 20, 30, 40]);
+        assert_eq!(
+            BinaryMap::from_sorted_vec(vec![(1, 'a'), (3, 'b'), (3, 'c')]),
+            Err(FromSortedError::Duplicate { index: 2 })
+        );
+
+        // SAFETY: the keys are sorted, unique and as many as the values.
+        let map: BinaryMap<_, _> = unsafe { BinaryMap::from_sorted_unchecked(vec![1], vec![2]) };
+        assert_eq!(map.get(&1), Some(&2));
+    }
+
+    #[test]
+    pub fn pair_iterators() {
+        let mut map = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+
+        let mut iter = map.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next_back(), Some((&4, &'d')));
+        assert_eq!(iter.next(), Some((&1, &'a')));
+        assert_eq!(iter.len(), 2);
+
+        for (k, v) in &mut map {
+            if k % 2 == 0 {
+                *v = v.to_ascii_uppercase();
+            }
+        }
+        for v in map.values_mut().rev().take(1) {
+            *v = 'e';
+        }
+        let mut keys = vec![];
+        for (k, _) in &map {
+            keys.push(*k);
+        }
+        assert_eq!(keys, vec![1, 2, 3, 4]);
+
+        let mut into_iter = map.into_iter();
+        assert_eq!(into_iter.len(), 4);
+        assert_eq!(into_iter.next_back(), Some((4, 'e')));
+        assert_eq!(
+            into_iter.collect::<Vec<_>>(),
+            vec![(1, 'a'), (2, 'B'), (3, 'c')]
+        );
     }
 
-    // #[test]
-    // pub fn extend() {
-    //     let mut set = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
-    //     set.extend([(1, 'e'), (2, 'f'), (3, 'g'), (4, 'h'), (5, 'i'), (7, 'j'), (9, 'k')]);
+    #[test]
+    pub fn extend() {
+        let mut set = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+        set.extend([(7, 'j'), (1, 'e'), (9, 'k'), (5, 'i'), (3, 'g'), (5, 'l')]);
 
-    //     assert_eq!(set.as_ref(), &vec![1, 2, 3, 4, 5, 6, 7, 8, 9])
-    // }
+        assert_eq!(set.keys(), &vec![1, 2, 3, 4, 5, 7, 9]);
+        assert_eq!(set.values(), &vec!['e', 'b', 'g', 'd', 'l', 'j', 'k']);
+    }
+
+    #[test]
+    pub fn extend_matches_insert() {
+        let pairs: Vec<(u32, u32)> = (0..200).map(|x| ((x * 37) % 101, x)).collect();
+
+        let mut extended = BinaryMap::from_iter((0..50).map(|x| (x * 3, 0)));
+        let mut inserted = extended.clone();
+        extended.extend(pairs.iter().map(|(k, v)| (k, v)));
+        for (k, v) in pairs {
+            inserted.insert(k, v);
+        }
+
+        assert_eq!(extended, inserted);
+    }
 
     #[test]
     pub fn insert() {
@@ -368,4 +1220,373 @@ pub mod tests {
 
         assert_eq!(set.values(), &vec![])
     }
+
+    #[test]
+    pub fn entry() {
+        let mut map = BinaryMap::from_iter([(1, 10), (3, 30)]);
+
+        *map.entry(1).or_insert(0) += 1;
+        *map.entry(2).or_insert(20) += 1;
+        map.entry(3).and_modify(|v| *v += 1).or_insert(0);
+        map.entry(4).and_modify(|v| *v += 1).or_default();
+        map.entry(5).or_insert_with(|| 50);
+        map.entry(6).or_insert_with_key(|k| k * 10);
+
+        assert_eq!(map.keys(), &vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(map.values(), &vec![11, 21, 31, 0, 50, 60]);
+        assert_eq!(map.entry(7).key(), &7);
+    }
+
+    #[test]
+    pub fn occupied_entry() {
+        let mut map = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c')]);
+
+        let Entry::Occupied(mut entry) = map.entry(2) else {
+            panic!("Should have been occupied")
+        };
+        assert_eq!(entry.key(), &2);
+        assert_eq!(entry.insert('d'), 'b');
+        assert_eq!(entry.get(), &'d');
+        assert_eq!(entry.remove(), 'd');
+
+        assert_eq!(map.keys(), &vec![1, 3]);
+        assert_eq!(map.values(), &vec!['a', 'c']);
+    }
+
+    #[test]
+    pub fn vacant_entry() {
+        let mut map = BinaryMap::from_iter([(1, 'a'), (3, 'c')]);
+
+        let Entry::Vacant(entry) = map.entry(2) else {
+            panic!("Should have been vacant")
+        };
+        assert_eq!(entry.key(), &2);
+        assert_eq!(entry.insert('b'), &mut 'b');
+
+        assert_eq!(map.keys(), &vec![1, 2, 3]);
+        assert_eq!(map.values(), &vec!['a', 'b', 'c']);
+    }
+
+    #[test]
+    pub fn range_double_ended() {
+        let map = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd'), (5, 'e')]);
+
+        let mut range = map.range(2..5);
+        assert_eq!(range.len(), 3);
+        assert_eq!(range.next_back(), Some((&4, &'d')));
+        assert_eq!(range.len(), 2);
+        assert_eq!(
+            map.range(..).rev().map(|x| *x.0).collect::<Vec<_>>(),
+            vec![5, 4, 3, 2, 1]
+        );
+        assert_eq!(map.range(..).nth_back(1), Some((&4, &'d')));
+    }
+
+    #[test]
+    pub fn range_mut() {
+        let mut map = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+
+        for (_, v) in map.range_mut(2..=3) {
+            *v = v.to_ascii_uppercase();
+        }
+        assert_eq!(map.range_mut(..).len(), 4);
+
+        assert_eq!(map.values(), &vec!['a', 'B', 'C', 'd']);
+    }
+
+    #[test]
+    pub fn borrowed_lookups() {
+        let mut map = BinaryMap::from_iter([("a".to_string(), 1), ("b".to_string(), 2)]);
+
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get_key_value("b"), Some((&"b".to_string(), &2)));
+        if let Some(v) = map.get_mut("b") {
+            *v = 3;
+        }
+        let range = (Bound::Included("a"), Bound::Excluded("c"));
+        assert_eq!(
+            map.range::<str, _>(range).map(|x| *x.1).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(map.remove("a"), Some(1));
+        assert_eq!(map.get("a"), None);
+    }
+
+    #[test]
+    pub fn comparable_lookups() {
+        use crate::comparable::Equivalent;
+        use std::cmp::Ordering;
+
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Query<'a>(u32, &'a str);
+
+        impl Equivalent<(u32, String)> for Query<'_> {
+            fn equivalent(&self, key: &(u32, String)) -> bool {
+                self.0 == key.0 && self.1 == key.1
+            }
+        }
+
+        impl Comparable<(u32, String)> for Query<'_> {
+            fn compare(&self, key: &(u32, String)) -> Ordering {
+                self.0.cmp(&key.0).then_with(|| self.1.cmp(key.1.as_str()))
+            }
+        }
+
+        let mut map = BinaryMap::from_iter([
+            ((1, "a".to_string()), 'a'),
+            ((1, "b".to_string()), 'b'),
+            ((2, "a".to_string()), 'c'),
+        ]);
+
+        assert_eq!(map.get_by(&Query(1, "b")), Some(&'b'));
+        assert_eq!(map.get_by(&Query(1, "c")), None);
+        assert_eq!(
+            map.get_key_value_by(&Query(2, "a")),
+            Some((&(2, "a".to_string()), &'c'))
+        );
+        assert_eq!(
+            map.range_by(Query(1, "b")..Query(3, ""))
+                .map(|x| *x.1)
+                .collect::<Vec<_>>(),
+            vec!['b', 'c']
+        );
+        if let Some(v) = map.get_mut_by(&Query(1, "a")) {
+            *v = 'd';
+        }
+        assert_eq!(map.remove_by(&Query(1, "a")), Some('d'));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start and end are equal and excluded")]
+    pub fn range_by_equal_excluded_bounds() {
+        use std::ops::Bound;
+
+        let map = BinaryMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c')]);
+        map.range_by((Bound::Excluded(2), Bound::Excluded(2)));
+    }
+
+    #[test]
+    pub fn reverse_comparator() {
+        use crate::compare::Reverse;
+
+        let mut map = BinaryMapBy::from_iter_with_comparator(
+            [(1, 'a'), (3, 'c'), (2, 'b')],
+            Reverse(Natural),
+        );
+        assert_eq!(map.keys(), &vec![3, 2, 1]);
+
+        map.insert(4, 'd');
+        map.extend([(0, 'z'), (2, 'B')]);
+        assert_eq!(map.keys(), &vec![4, 3, 2, 1, 0]);
+        assert_eq!(map.get(&2), Some(&'B'));
+
+        *map.entry(1).or_insert('x') = 'A';
+        assert_eq!(
+            map.range((Bound::Included(3), Bound::Included(1)))
+                .map(|x| *x.1)
+                .collect::<Vec<_>>(),
+            vec!['c', 'B', 'A']
+        );
+    }
+
+    #[test]
+    pub fn reverse_comparator_lookups() {
+        use crate::compare::Reverse;
+
+        let map = BinaryMapBy::from_iter_with_comparator(
+            [(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')],
+            Reverse(Natural),
+        );
+        assert_eq!(map.get(&4), Some(&'d'));
+        assert_eq!(map.get_key_value(&1), Some((&1, &'a')));
+        assert_eq!(
+            map.range((Bound::Included(4), Bound::Included(2)))
+                .map(|x| *x.1)
+                .collect::<Vec<_>>(),
+            vec!['d', 'c', 'b']
+        );
+    }
+
+    #[test]
+    pub fn closure_comparator() {
+        let by_len = |a: &&str, b: &&str| a.len().cmp(&b.len());
+        let mut map = BinaryMapBy::with_comparator(by_len);
+        map.insert("ccc", 3);
+        map.insert("a", 1);
+        assert_eq!(map.insert("b", 2), Some(1));
+
+        assert_eq!(map.keys(), &vec!["a", "ccc"]);
+        assert_eq!(map.get(&"zzz"), Some(&3));
+    }
+
+    #[test]
+    pub fn bulk_mutation() {
+        let mut map = BinaryMap::from_iter((0..10).map(|k| (k, k * 10)));
+        map.retain(|k, v| {
+            *v += 1;
+            k % 3 != 0
+        });
+        assert_eq!(map.keys(), &vec![1, 2, 4, 5, 7, 8]);
+        assert_eq!(map.get(&4), Some(&41));
+
+        let odd: Vec<_> = map.extract_if(|k, _| k % 2 == 1).collect();
+        assert_eq!(odd, vec![(1, 11), (5, 51), (7, 71)]);
+        assert_eq!(map.keys(), &vec![2, 4, 8]);
+
+        let mut early = map.extract_if(|_, _| true);
+        assert_eq!(early.next(), Some((2, 21)));
+        drop(early);
+        assert_eq!(map.keys(), &vec![4, 8]);
+        assert_eq!(map.values(), &vec![41, 81]);
+
+        let mut map = BinaryMap::from_iter((0..10).map(|k| (k, -k)));
+        let drained: Vec<_> = map.drain_range(3..6).collect();
+        assert_eq!(drained, vec![(3, -3), (4, -4), (5, -5)]);
+        let high = map.split_off(&7);
+        assert_eq!(map.keys(), &vec![0, 1, 2, 6]);
+        assert_eq!(high.keys(), &vec![7, 8, 9]);
+        assert_eq!(map.drain().next_back(), Some((6, -6)));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    pub fn append_prefers_other() {
+        let mut map = BinaryMap::from_iter([(1, "a"), (3, "a"), (5, "a")]);
+        let mut other = BinaryMap::from_iter([(2, "b"), (3, "b"), (6, "b")]);
+        map.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(map.keys(), &vec![1, 2, 3, 5, 6]);
+        assert_eq!(map.values(), &vec!["a", "b", "b", "a", "b"]);
+    }
+
+    #[test]
+    pub fn small_map_stays_inline() {
+        let mut map: SmallBinaryMap<u32, char, 4> =
+            [(3, 'c'), (1, 'a'), (3, 'C')].into_iter().collect();
+        assert_eq!(map.insert(2, 'b'), None);
+        *map.entry(4).or_insert('d') = 'D';
+        map.extend([(1, 'A')]);
+        assert!(!map.key_buffer().spilled());
+        assert!(map
+            .iter()
+            .eq([(&1, &'A'), (&2, &'b'), (&3, &'C'), (&4, &'D')]));
+
+        map.retain(|k, _| k % 2 == 0);
+        map.extend([(5, 'e'), (6, 'f'), (7, 'g')]);
+        assert!(map.key_buffer().spilled());
+        assert_eq!(map.remove(&5), Some('e'));
+        let map = map.into_storage::<VecStore>();
+        assert_eq!(map.keys(), &vec![2, 4, 6, 7]);
+        assert_eq!(map.values(), &vec!['b', 'D', 'f', 'g']);
+    }
+
+    #[test]
+    pub fn retain_panic_keeps_lockstep() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut map = BinaryMap::from_iter((0..8).map(|k| (k, k.to_string())));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            map.retain(|k, _| {
+                assert!(*k != 5);
+                k % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(map.keys(), &vec![0, 2, 4, 5, 6, 7]);
+        assert_eq!(map.values(), &vec!["0", "2", "4", "5", "6", "7"]);
+    }
+
+    /// Compares like `u32`, but panics when either side is 13.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Touchy(u32);
+
+    impl Ord for Touchy {
+        fn cmp(&self, other: &Self) -> Ordering {
+            assert!(self.0 != 13 && other.0 != 13, "compared 13");
+            self.0.cmp(&other.0)
+        }
+    }
+
+    impl PartialOrd for Touchy {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    #[test]
+    pub fn insert_panicking_ord() {
+        let mut map = BinaryMap::from_iter((0..10).map(|k| (Touchy(k), k * 10)));
+        assert_lockstep_after(
+            &mut map,
+            |map| {
+                map.insert(Touchy(13), 130);
+            },
+            |k| k.0 * 10,
+            "compared 13",
+        );
+    }
+
+    #[test]
+    pub fn append_panicking_ord() {
+        let mut map = BinaryMap::from_iter((0..10).map(|k| (Touchy(k * 2), k * 20)));
+        let mut other = BinaryMap::from_iter([1, 5, 9].map(|k| (Touchy(k), k * 10)));
+        other.keys.push(Touchy(13));
+        other.values.push(130);
+        assert_lockstep_after(
+            &mut map,
+            |map| map.append(&mut other),
+            |k| k.0 * 10,
+            "compared 13",
+        );
+    }
+
+    #[test]
+    pub fn extend_panicking_drop() {
+        let mut map = BinaryMap::from_iter((0..10).map(|k| (k, Brittle(k * 10, k == 5))));
+        assert_lockstep_after(
+            &mut map,
+            |map| map.extend([20, 5, 3].map(|k| (k, Brittle(k * 10, false)))),
+            |k| Brittle(k * 10, false),
+            "dropped a brittle key",
+        );
+    }
+
+    #[test]
+    pub fn remove_panicking_drop() {
+        let mut map = BinaryMap::from_iter((0..6).map(|k| (Brittle(k, k == 2), k * 10)));
+        assert_lockstep_after(
+            &mut map,
+            |map| {
+                map.remove(&2);
+            },
+            |k| k.0 * 10,
+            "dropped a brittle key",
+        );
+    }
+
+    #[test]
+    pub fn clear_panicking_drop() {
+        let mut map = BinaryMap::from_iter((0..6).map(|k| (Brittle(k, k == 2), k * 10)));
+        assert_lockstep_after(
+            &mut map,
+            |map| map.clear(),
+            |k| k.0 * 10,
+            "dropped a brittle key",
+        );
+    }
+
+    #[test]
+    pub fn entry_remove_panicking_drop() {
+        let mut map = BinaryMap::from_iter((0..6).map(|k| (Brittle(k, k == 4), k * 10)));
+        assert_lockstep_after(
+            &mut map,
+            |map| match map.entry(Brittle(4, false)) {
+                Entry::Occupied(entry) => drop(entry.remove()),
+                Entry::Vacant(_) => unreachable!(),
+            },
+            |k| k.0 * 10,
+            "dropped a brittle key",
+        );
+    }
 }
@@ -0,0 +1,236 @@
+use std::fmt;
+use std::iter::{FusedIterator, Zip};
+use std::{ptr, slice, vec};
+
+use crate::storage::{SortedStorage, VecStore};
+
+/// Iterator over the entries of a [`BinaryMap`](crate::map::BinaryMap), returned by `iter`.
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    pub(super) inner: Zip<slice::Iter<'a, K>, slice::Iter<'a, V>>,
+}
+
+/// Mutable iterator over the entries of a [`BinaryMap`](crate::map::BinaryMap), returned by `iter_mut`.
+#[derive(Debug)]
+pub struct IterMut<'a, K, V> {
+    pub(super) inner: Zip<slice::Iter<'a, K>, slice::IterMut<'a, V>>,
+}
+
+/// Owning iterator over the entries of a [`BinaryMap`](crate::map::BinaryMap), returned by `into_iter`.
+pub struct IntoIter<K, V, S: SortedStorage = VecStore> {
+    pub(super) inner: Zip<BufferIntoIter<K, S>, BufferIntoIter<V, S>>,
+}
+
+type BufferIntoIter<T, S> = <<S as SortedStorage>::Buffer<T> as IntoIterator>::IntoIter;
+
+/// Mutable iterator over the values of a [`BinaryMap`](crate::map::BinaryMap), returned by `values_mut`.
+#[derive(Debug)]
+pub struct ValuesMut<'a, V> {
+    pub(super) inner: slice::IterMut<'a, V>,
+}
+
+/// Iterator over a sub-range of a [`BinaryMap`](crate::map::BinaryMap), returned by `range`.
+#[derive(Debug)]
+pub struct Range<'a, K, V> {
+    pub(super) inner: Zip<slice::Iter<'a, K>, slice::Iter<'a, V>>,
+}
+
+/// Mutable iterator over a sub-range of a [`BinaryMap`](crate::map::BinaryMap), returned by `range_mut`.
+#[derive(Debug)]
+pub struct RangeMut<'a, K, V> {
+    pub(super) inner: Zip<slice::Iter<'a, K>, slice::IterMut<'a, V>>,
+}
+
+/// Draining iterator over the entries of a [`BinaryMap`](crate::map::BinaryMap), returned by `drain` and `drain_range`.
+#[derive(Debug)]
+pub struct Drain<'a, K, V> {
+    pub(super) inner: Zip<vec::Drain<'a, K>, vec::Drain<'a, V>>,
+}
+
+/// Iterator that removes the entries of a [`BinaryMap`](crate::map::BinaryMap) matching a predicate,
+/// returned by `extract_if`.
+///
+/// Both vectors have their length zeroed while it runs. Kept entries are moved down over
+/// extracted ones, and on drop the unvisited tail is moved down after them, so the keys and
+/// values stay in lockstep even if the predicate panics.
+pub struct ExtractIf<'a, K, V, F> {
+    keys: &'a mut Vec<K>,
+    values: &'a mut Vec<V>,
+    extract: F,
+    kept: usize,
+    read: usize,
+    len: usize,
+}
+
+impl<'a, K, V, F> ExtractIf<'a, K, V, F> {
+    pub(super) fn new(keys: &'a mut Vec<K>, values: &'a mut Vec<V>, extract: F) -> Self {
+        let len = keys.len();
+        debug_assert_eq!(len, values.len());
+        // SAFETY: no elements are dropped through the vectors until the lengths are restored.
+        unsafe {
+            keys.set_len(0);
+            values.set_len(0);
+        }
+        Self {
+            keys,
+            values,
+            extract,
+            kept: 0,
+            read: 0,
+            len,
+        }
+    }
+}
+
+impl<K, V, F: FnMut(&K, &mut V) -> bool> Iterator for ExtractIf<'_, K, V, F> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.read < self.len {
+            let (keys, values) = (self.keys.as_mut_ptr(), self.values.as_mut_ptr());
+            // SAFETY: entries from `read` onwards have not been moved.
+            let (key, value) = unsafe { (&*keys.add(self.read), &mut *values.add(self.read)) };
+            let extract = (self.extract)(key, value);
+            self.read += 1;
+            // SAFETY: the entry at `read - 1` is moved exactly once, either out or down to `kept`,
+            // which is below it unless they are the same slot.
+            unsafe {
+                if extract {
+                    return Some((ptr::read(key), ptr::read(value)));
+                }
+                if self.kept != self.read - 1 {
+                    ptr::copy_nonoverlapping(key, keys.add(self.kept), 1);
+                    ptr::copy_nonoverlapping(value, values.add(self.kept), 1);
+                }
+            }
+            self.kept += 1;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.read))
+    }
+}
+
+impl<K, V, F> Drop for ExtractIf<'_, K, V, F> {
+    fn drop(&mut self) {
+        let tail = self.len - self.read;
+        // SAFETY: the unvisited tail is initialized and moves down onto extracted or moved-out slots.
+        unsafe {
+            let (keys, values) = (self.keys.as_mut_ptr(), self.values.as_mut_ptr());
+            ptr::copy(keys.add(self.read), keys.add(self.kept), tail);
+            ptr::copy(values.add(self.read), values.add(self.kept), tail);
+            self.keys.set_len(self.kept + tail);
+            self.values.set_len(self.kept + tail);
+        }
+    }
+}
+
+impl<K, V, S: SortedStorage> fmt::Debug for IntoIter<K, V, S>
+where
+    BufferIntoIter<K, S>: fmt::Debug,
+    BufferIntoIter<V, S>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, F> fmt::Debug for ExtractIf<'_, K, V, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractIf")
+            .field("remaining", &(self.len - self.read))
+            .finish()
+    }
+}
+
+impl<K, V, F: FnMut(&K, &mut V) -> bool> FusedIterator for ExtractIf<'_, K, V, F> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for IntoIter<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V> Clone for Range<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V, S: SortedStorage> Iterator for IntoIter<K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, S: SortedStorage> DoubleEndedIterator for IntoIter<K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K, V, S: SortedStorage> ExactSizeIterator for IntoIter<K, V, S> {}
+impl<K, V, S: SortedStorage> FusedIterator for IntoIter<K, V, S> {}
+
+/// Forwards the iterator traits to the `inner` iterator.
+macro_rules! delegate_iterator {
+    ($name:ident<$($param:tt),*>, $item:ty) => {
+        impl<$($param),*> Iterator for $name<$($param),*> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.inner.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+
+            fn nth(&mut self, n: usize) -> Option<Self::Item> {
+                self.inner.nth(n)
+            }
+        }
+
+        impl<$($param),*> DoubleEndedIterator for $name<$($param),*> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.inner.next_back()
+            }
+
+            fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+                self.inner.nth_back(n)
+            }
+        }
+
+        impl<$($param),*> ExactSizeIterator for $name<$($param),*> {}
+        impl<$($param),*> FusedIterator for $name<$($param),*> {}
+    };
+}
+
+delegate_iterator!(Iter<'a, K, V>, (&'a K, &'a V));
+delegate_iterator!(IterMut<'a, K, V>, (&'a K, &'a mut V));
+delegate_iterator!(ValuesMut<'a, V>, &'a mut V);
+delegate_iterator!(Range<'a, K, V>, (&'a K, &'a V));
+delegate_iterator!(RangeMut<'a, K, V>, (&'a K, &'a mut V));
+delegate_iterator!(Drain<'a, K, V>, (K, V));
@@ -0,0 +1,458 @@
+//! Merges over sorted, deduplicated vectors, shared by the sets and maps.
+//! Those that only remove elements work on any [`Buffer`], in place. Those that add map entries
+//! take the key of each stored element through a projection, so that maps storing parallel keys
+//! and values and maps storing pairs share them.
+
+use std::cmp::Ordering;
+use std::ptr;
+
+use crate::buffer::Retain;
+use crate::storage::Buffer;
+
+/// When one side is this many times longer than the other, runs are found by galloping.
+pub(crate) const GALLOP_RATIO: usize = 8;
+
+pub(crate) fn should_gallop(a: usize, b: usize) -> bool {
+    a.max(b) > a.min(b).saturating_mul(GALLOP_RATIO)
+}
+
+/// Returns the index of the first element for which `pred` is false, probing exponentially from the front.
+pub(crate) fn gallop<T>(slice: &[T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let mut step = 1;
+    while step <= slice.len() && pred(&slice[step - 1]) {
+        step *= 2;
+    }
+    let lo = step / 2;
+    let hi = step.min(slice.len());
+    lo + slice[lo..hi].partition_point(pred)
+}
+
+/// Returns the index of the first element for which `pred` is false, probing exponentially from the back.
+pub(crate) fn gallop_back<T>(slice: &[T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let len = slice.len();
+    let mut step = 1;
+    while step <= len && !pred(&slice[len - step]) {
+        step *= 2;
+    }
+    let lo = len.saturating_sub(step);
+    let hi = len - step / 2;
+    lo + slice[lo..hi].partition_point(pred)
+}
+
+/// Number of leading elements for which `pred` holds.
+fn run<T>(slice: &[T], mut pred: impl FnMut(&T) -> bool, gallop: bool) -> usize {
+    if gallop {
+        self::gallop(slice, pred)
+    } else {
+        slice.iter().take_while(|x| pred(x)).count()
+    }
+}
+
+/// Number of trailing elements for which `pred` does not hold.
+fn run_back<T>(slice: &[T], mut pred: impl FnMut(&T) -> bool, gallop: bool) -> usize {
+    if gallop {
+        slice.len() - gallop_back(slice, pred)
+    } else {
+        slice.iter().rev().take_while(|x| !pred(x)).count()
+    }
+}
+
+/// Keeps `a ∪ b` in `a`. Elements already in `a` win over equal elements of `b`.
+pub(crate) fn union<T>(a: &mut Vec<T>, mut b: Vec<T>, compare: impl Fn(&T, &T) -> Ordering) {
+    if let (Some(x), Some(y)) = (a.last(), b.first()) {
+        if compare(x, y).is_lt() {
+            a.append(&mut b);
+            return;
+        }
+    }
+    a.reserve(b.len());
+    union_back(BackMerge::new(a, &mut b), compare);
+}
+
+/// Sorts and deduplicates `vec[start..]`, then merges it into the sorted, deduplicated
+/// `vec[..start]` without a second buffer. Elements before `start` win over equal ones after
+/// it, and among equal ones after it the first wins. A panic from `compare` drops the new
+/// elements it has not merged and leaves the rest sorted.
+pub(crate) fn union_tail<T>(vec: &mut Vec<T>, start: usize, compare: impl Fn(&T, &T) -> Ordering) {
+    let mut merge = BackMerge::in_place(vec, start);
+    merge.b_unread_mut().sort_by(&compare);
+    merge.dedup_b(|a, b| compare(a, b).is_eq());
+    union_back(merge, compare);
+}
+
+/// Merges the unread elements of `merge`, dropping those of `b` that are already in `a`.
+fn union_back<T>(mut merge: BackMerge<'_, T>, compare: impl Fn(&T, &T) -> Ordering) {
+    let gallop = should_gallop(merge.a_len, merge.b_len);
+    while merge.a_len > 0 && merge.b_len > 0 {
+        // SAFETY: both indices are below the number of unread elements.
+        let a_last = unsafe { &*merge.a_ptr.add(merge.a_len - 1) };
+        let b_last = unsafe { &*merge.b_ptr.add(merge.b_len - 1) };
+        // without galloping, stepping one element at a time compares each element only once
+        match compare(a_last, b_last) {
+            Ordering::Greater if gallop => {
+                let count = run_back(merge.a_unread(), |x| compare(x, b_last).is_le(), true);
+                merge.take_a(count);
+            }
+            Ordering::Less if gallop => {
+                let count = run_back(merge.b_unread(), |x| compare(x, a_last).is_le(), true);
+                merge.take_b(count);
+            }
+            Ordering::Greater => merge.take_a(1),
+            Ordering::Less => merge.take_b(1),
+            Ordering::Equal => {
+                merge.drop_b();
+                merge.take_a(1);
+            }
+        }
+    }
+    let remaining = merge.b_len;
+    merge.take_b(remaining);
+}
+
+/// Keeps `a ∩ b` in `a`.
+pub(crate) fn intersection<T, B: Buffer<T>>(
+    a: &mut B,
+    mut b: &[T],
+    compare: impl Fn(&T, &T) -> Ordering,
+) {
+    let gallop = should_gallop(a.len(), b.len());
+    let mut retain = Retain::new(a, B::truncate);
+
+    while let (Some(x), Some(y)) = (retain.unread().first(), b.first()) {
+        match compare(x, y) {
+            Ordering::Less => {
+                let count = run(retain.unread(), |x| compare(x, y).is_lt(), gallop);
+                retain.skip(count);
+            }
+            Ordering::Greater => b = &b[run(b, |y| compare(y, x).is_lt(), gallop)..],
+            Ordering::Equal => {
+                retain.keep(1);
+                b = &b[1..];
+            }
+        }
+    }
+    let remaining = retain.unread().len();
+    retain.skip(remaining);
+}
+
+/// Keeps `a \ b` in `a`.
+pub(crate) fn difference<T, B: Buffer<T>>(
+    a: &mut B,
+    mut b: &[T],
+    compare: impl Fn(&T, &T) -> Ordering,
+) {
+    let gallop = should_gallop(a.len(), b.len());
+    let mut retain = Retain::new(a, B::truncate);
+
+    while let (Some(x), Some(y)) = (retain.unread().first(), b.first()) {
+        match compare(x, y) {
+            Ordering::Less => {
+                let count = run(retain.unread(), |x| compare(x, y).is_lt(), gallop);
+                retain.keep(count);
+            }
+            Ordering::Greater => b = &b[run(b, |y| compare(y, x).is_lt(), gallop)..],
+            Ordering::Equal => {
+                retain.skip(1);
+                b = &b[1..];
+            }
+        }
+    }
+}
+
+/// Removes the elements that `a` and `b` share from both, leaving `a \ b` and `b \ a`.
+pub(crate) fn remove_shared<T, B: Buffer<T>>(
+    a: &mut B,
+    b: &mut B,
+    compare: impl Fn(&T, &T) -> Ordering,
+) {
+    let gallop = should_gallop(a.len(), b.len());
+    let mut a = Retain::new(a, B::truncate);
+    let mut b = Retain::new(b, B::truncate);
+
+    while let (Some(x), Some(y)) = (a.unread().first(), b.unread().first()) {
+        match compare(x, y) {
+            Ordering::Less => {
+                let count = run(a.unread(), |x| compare(x, y).is_lt(), gallop);
+                a.keep(count);
+            }
+            Ordering::Greater => {
+                let count = run(b.unread(), |y| compare(y, x).is_lt(), gallop);
+                b.keep(count);
+            }
+            Ordering::Equal => {
+                a.skip(1);
+                b.skip(1);
+            }
+        }
+    }
+}
+
+/// Inserts each `items[j]` before the element at `indices[j]` of the original `vec`,
+/// moving every element at most once. `indices` must be sorted.
+pub(crate) fn insert_at<T>(vec: &mut Vec<T>, indices: &[usize], mut items: Vec<T>) {
+    let old_len = vec.len();
+    let count = items.len();
+    assert_eq!(indices.len(), count);
+    assert!(indices.windows(2).all(|w| w[0] <= w[1]));
+    assert!(indices.last().is_none_or(|&index| index <= old_len));
+
+    vec.reserve(count);
+    // SAFETY: the indices are checked above, and nothing in the loop can panic,
+    // so every element is moved exactly once before the lengths are set.
+    unsafe {
+        items.set_len(0);
+        let src = items.as_ptr();
+        let dst = vec.as_mut_ptr();
+        let mut end = old_len;
+        for j in (0..count).rev() {
+            let index = *indices.get_unchecked(j);
+            ptr::copy(dst.add(index), dst.add(index + j + 1), end - index);
+            ptr::copy_nonoverlapping(src.add(j), dst.add(index + j), 1);
+            end = index;
+        }
+        vec.set_len(old_len + count);
+    }
+}
+
+/// Sorts `batch` by key, keeping the first key and the last value of each run of equal keys,
+/// then finds each entry among the sorted `slots` by galloping on from the previous one.
+/// Values whose keys are present go to `replace` with the slot and its index, the other entries
+/// go to `insert`, and the indices to insert those at are returned for [`insert_at`].
+pub(crate) fn place_batch<T, K, V>(
+    slots: &mut [T],
+    key: impl Fn(&T) -> &K,
+    mut batch: Vec<(K, V)>,
+    compare: impl Fn(&K, &K) -> Ordering,
+    mut replace: impl FnMut(&mut T, usize, V),
+    mut insert: impl FnMut(K, V),
+) -> Vec<usize> {
+    batch.sort_by(|a, b| compare(&a.0, &b.0));
+    // later values win, as they would with repeated inserts
+    batch.dedup_by(|next, kept| {
+        if compare(&next.0, &kept.0).is_eq() {
+            std::mem::swap(&mut next.1, &mut kept.1);
+            true
+        } else {
+            false
+        }
+    });
+
+    let mut indices = Vec::with_capacity(batch.len());
+    let mut start = 0;
+    for (k, v) in batch {
+        start += gallop(&slots[start..], |slot| compare(key(slot), &k).is_lt());
+        match slots.get_mut(start) {
+            Some(slot) if compare(key(slot), &k).is_eq() => replace(slot, start, v),
+            _ => {
+                indices.push(start);
+                insert(k, v);
+            }
+        }
+    }
+    indices
+}
+
+/// Merges two runs of entries sorted and deduplicated by key into `push`, in order.
+/// Where both have a key, the entry from `right` wins, as in `BTreeMap::append`.
+pub(crate) fn union_entries<K, V>(
+    left: impl Iterator<Item = (K, V)>,
+    right: impl Iterator<Item = (K, V)>,
+    compare: impl Fn(&K, &K) -> Ordering,
+    mut push: impl FnMut(K, V),
+) {
+    let (mut left, mut right) = (left.peekable(), right.peekable());
+    loop {
+        let next = match (left.peek(), right.peek()) {
+            (Some(a), Some(b)) => match compare(&a.0, &b.0) {
+                Ordering::Less => left.next(),
+                Ordering::Greater => right.next(),
+                Ordering::Equal => {
+                    left.next();
+                    right.next()
+                }
+            },
+            (Some(_), None) => left.next(),
+            (None, _) => right.next(),
+        };
+        let Some((key, value)) = next else { break };
+        push(key, value);
+    }
+}
+
+/// Merges `b` into the spare capacity of `a` back to front.
+/// Both vectors have their length zeroed while merging. On drop, the written tail is moved
+/// down onto the unread part of `a` and the unread part of `b` is handed back to `b`, or
+/// dropped if `b` was the tail of `a`.
+struct BackMerge<'a, T> {
+    a: &'a mut Vec<T>,
+    b: Option<&'a mut Vec<T>>,
+    a_ptr: *mut T,
+    b_ptr: *mut T,
+    a_len: usize,
+    b_len: usize,
+    write: usize,
+    end: usize,
+}
+
+impl<'a, T> BackMerge<'a, T> {
+    fn new(a: &'a mut Vec<T>, b: &'a mut Vec<T>) -> Self {
+        let a_len = a.len();
+        let b_len = b.len();
+        debug_assert!(a.capacity() >= a_len + b_len);
+        // SAFETY: no elements are dropped through the vectors until the guard restores their lengths.
+        unsafe {
+            a.set_len(0);
+            b.set_len(0);
+        }
+        Self {
+            a_ptr: a.as_mut_ptr(),
+            b_ptr: b.as_mut_ptr(),
+            a,
+            b: Some(b),
+            a_len,
+            b_len,
+            write: a_len + b_len,
+            end: a_len + b_len,
+        }
+    }
+
+    /// Treats the elements of `vec` from `start` on as `b`, first moving them past the end of
+    /// the merged length so that nothing is written over them before they are read.
+    fn in_place(vec: &'a mut Vec<T>, start: usize) -> Self {
+        let a_len = start;
+        let b_len = vec.len() - start;
+        vec.reserve(b_len);
+        // SAFETY: the capacity holds `a_len + 2 * b_len` elements, so the tail moves into spare
+        // capacity, and no elements are dropped through the vector until the guard restores its length.
+        unsafe {
+            vec.set_len(0);
+            let a_ptr = vec.as_mut_ptr();
+            let b_ptr = a_ptr.add(a_len + b_len);
+            ptr::copy_nonoverlapping(a_ptr.add(a_len), b_ptr, b_len);
+            Self {
+                a: vec,
+                b: None,
+                a_ptr,
+                b_ptr,
+                a_len,
+                b_len,
+                write: a_len + b_len,
+                end: a_len + b_len,
+            }
+        }
+    }
+
+    fn a_unread(&self) -> &[T] {
+        // SAFETY: the first `a_len` elements of `a` have not been moved.
+        unsafe { std::slice::from_raw_parts(self.a_ptr, self.a_len) }
+    }
+
+    fn b_unread(&self) -> &[T] {
+        // SAFETY: the first `b_len` elements of `b` have not been moved.
+        unsafe { std::slice::from_raw_parts(self.b_ptr, self.b_len) }
+    }
+
+    fn b_unread_mut(&mut self) -> &mut [T] {
+        // SAFETY: the first `b_len` elements of `b` have not been moved.
+        unsafe { std::slice::from_raw_parts_mut(self.b_ptr, self.b_len) }
+    }
+
+    /// Drops each unread element of `b` that `same` matches with the one kept before it.
+    fn dedup_b(&mut self, same: impl Fn(&T, &T) -> bool) {
+        let b = self.b_unread_mut();
+        let mut kept = b.len().min(1);
+        for read in 1..b.len() {
+            if !same(&b[read], &b[kept - 1]) {
+                b.swap(kept, read);
+                kept += 1;
+            }
+        }
+        let duplicates = ptr::slice_from_raw_parts_mut(b[kept..].as_mut_ptr(), b.len() - kept);
+        self.b_len = kept;
+        // SAFETY: the duplicates are initialized, and are no longer counted as unread.
+        unsafe { ptr::drop_in_place(duplicates) }
+    }
+
+    /// Moves the last `count` unread elements of `a` to the front of the written tail.
+    fn take_a(&mut self, count: usize) {
+        self.a_len -= count;
+        self.write -= count;
+        // SAFETY: the source is unread and the destination lies at or above it, inside the capacity.
+        unsafe {
+            ptr::copy(
+                self.a_ptr.add(self.a_len),
+                self.a_ptr.add(self.write),
+                count,
+            )
+        }
+    }
+
+    /// Moves the last `count` unread elements of `b` to the front of the written tail.
+    fn take_b(&mut self, count: usize) {
+        self.b_len -= count;
+        self.write -= count;
+        // SAFETY: the write position never falls below the unread part of `a`, and the unread
+        // part of `b` lies in another buffer or past the end of the merged length.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.b_ptr.add(self.b_len),
+                self.a_ptr.add(self.write),
+                count,
+            )
+        }
+    }
+
+    /// Drops the last unread element of `b`.
+    fn drop_b(&mut self) {
+        self.b_len -= 1;
+        // SAFETY: the element was unread and is no longer counted as such.
+        unsafe { ptr::drop_in_place(self.b_ptr.add(self.b_len)) }
+    }
+}
+
+impl<T> Drop for BackMerge<'_, T> {
+    fn drop(&mut self) {
+        let written = self.end - self.write;
+        // SAFETY: the written tail and the unread part of `a` are both initialized, and the
+        // unread part of `b` was never moved.
+        unsafe {
+            ptr::copy(
+                self.a_ptr.add(self.write),
+                self.a_ptr.add(self.a_len),
+                written,
+            );
+            self.a.set_len(self.a_len + written);
+            match &mut self.b {
+                Some(b) => b.set_len(self.b_len),
+                None => ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.b_ptr, self.b_len)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::merge::*;
+
+    #[test]
+    pub fn gallop_finds_partition_point() {
+        for len in 0..40 {
+            let slice: Vec<usize> = (0..len).collect();
+            for key in 0..=len {
+                assert_eq!(gallop(&slice, |x| *x < key), key);
+                assert_eq!(gallop_back(&slice, |x| *x < key), key);
+            }
+        }
+    }
+
+    #[test]
+    pub fn insert_at_keeps_order() {
+        let mut vec = vec![10, 20, 30];
+        insert_at(&mut vec, &[0, 0, 2, 3, 3], vec![1, 2, 25, 31, 32]);
+        assert_eq!(vec, vec![1, 2, 10, 20, 25, 30, 31, 32]);
+
+        let mut empty: Vec<String> = vec![];
+        insert_at(&mut empty, &[0], vec!["a".to_string()]);
+        assert_eq!(empty, vec!["a".to_string()]);
+    }
+}
@@ -0,0 +1,898 @@
+use std::cmp::Ordering;
+use std::ops::RangeBounds;
+use std::vec;
+
+use cc_traits::{
+    covariant_item_mut, covariant_item_ref, covariant_key_ref, simple_collection_mut,
+    simple_collection_ref, simple_keyed_ref, Capacity, Clear, Collection, CollectionMut,
+    CollectionRef, Get, GetKeyValue, GetKeyValueMut, GetMut, Keyed, KeyedRef, Len, MapInsert,
+    MapIter, MapIterMut, Remove, Reserve, SimpleCollectionMut, SimpleCollectionRef, SimpleKeyedRef,
+    WithCapacity,
+};
+
+use crate::buffer::Retain;
+use crate::comparable::Comparable;
+use crate::compare::{Compare, Natural};
+use crate::error::{DuplicateKeyError, FromSortedError};
+use crate::map::{BinaryMap, DuplicatePolicy};
+use crate::storage::{Buffer, SmallStore, SortedStorage, VecStore};
+use crate::{merge, search};
+
+mod entry;
+mod iter;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use iter::{Iter, IterMut, Keys, Values, ValuesMut};
+
+/// A map stored as one sorted buffer of key-value pairs, ordered by the comparator `C`.
+/// The storage `S` picks the buffer, which is a `Vec` by default.
+///
+/// Unlike [`BinaryMapBy`](crate::map::BinaryMapBy), a lookup touches one allocation rather
+/// than two, and the entries can be borrowed as a `&[(K, V)]`. Scans over the keys alone are
+/// slower, as they step over the values.
+#[derive(Debug, Clone, Default)]
+pub struct BinaryPairMapBy<K, V, C, S: SortedStorage = VecStore> {
+    entries: S::Buffer<(K, V)>,
+    compare: C,
+}
+
+/// A map stored as one sorted `Vec` of key-value pairs, in the natural order of its keys.
+pub type BinaryPairMap<K, V> = BinaryPairMapBy<K, V, Natural, VecStore>;
+
+/// A map that keeps up to `N` key-value pairs inline, in the natural order of its keys.
+pub type SmallBinaryPairMap<K, V, const N: usize> = BinaryPairMapBy<K, V, Natural, SmallStore<N>>;
+
+impl<K, V, C> BinaryPairMapBy<K, V, C> {
+    pub const fn with_comparator(compare: C) -> Self {
+        Self {
+            entries: Vec::new(),
+            compare,
+        }
+    }
+
+    /// Removes every entry and iterates over them in order.
+    pub fn drain(&mut self) -> vec::Drain<'_, (K, V)> {
+        self.entries.drain(..)
+    }
+
+    /// Removes the entries for which `extract` returns true as the iterator reaches them.
+    /// Entries it does not reach are kept.
+    pub fn extract_if<'a, F: FnMut(&K, &mut V) -> bool + 'a>(
+        &'a mut self,
+        mut extract: F,
+    ) -> impl Iterator<Item = (K, V)> + 'a {
+        self.entries.extract_if(.., move |(k, v)| extract(k, v))
+    }
+}
+
+impl<K, V, C, S: SortedStorage> BinaryPairMapBy<K, V, C, S> {
+    pub const fn comparator(&self) -> &C {
+        &self.compare
+    }
+
+    /// The entries, sorted by key.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.entries
+    }
+
+    pub fn into_vec(self) -> Vec<(K, V)> {
+        self.entries.into()
+    }
+
+    /// Moves the entries into the storage `S2`.
+    pub fn into_storage<S2: SortedStorage>(self) -> BinaryPairMapBy<K, V, C, S2> {
+        let entries: Vec<(K, V)> = self.entries.into();
+        BinaryPairMapBy {
+            entries: entries.into(),
+            compare: self.compare,
+        }
+    }
+
+    fn find<Q: ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        C: Compare<Q, K>,
+    {
+        search::find(&self.entries, key, |q, (k, _)| self.compare.compare(q, k))
+    }
+
+    fn find_range<Q, R>(&self, range: &R) -> std::ops::Range<usize>
+    where
+        C: Compare<Q, K> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(range, |a, b| self.compare.compare(a, b));
+        search::range(&self.entries, range, |q, (k, _)| self.compare.compare(q, k))
+    }
+}
+
+impl<K, V, C: Compare<K>> BinaryPairMapBy<K, V, C> {
+    /// Collects `iter`, keeping the last value for each key.
+    pub fn from_iter_with_comparator<I: IntoIterator<Item = (K, V)>>(iter: I, compare: C) -> Self {
+        Self::collect_with_comparator(iter, compare)
+    }
+
+    /// Wraps `vec` after checking in linear time that its keys are sorted and deduplicated
+    /// by `compare`.
+    pub fn from_sorted_vec_with_comparator(
+        vec: Vec<(K, V)>,
+        compare: C,
+    ) -> Result<Self, FromSortedError> {
+        search::check_sorted(&vec, |a, b| compare.compare(&a.0, &b.0))?;
+        Ok(Self {
+            entries: vec,
+            compare,
+        })
+    }
+
+    /// Collects `iter` after checking in linear time that its keys are sorted and deduplicated
+    /// by `compare`.
+    pub fn from_sorted_iter_with_comparator<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        compare: C,
+    ) -> Result<Self, FromSortedError> {
+        Self::from_sorted_vec_with_comparator(Vec::from_iter(iter), compare)
+    }
+
+    /// Removes the entries whose keys lie within `range` and iterates over them in order.
+    pub fn drain_range<Q, R>(&mut self, range: R) -> vec::Drain<'_, (K, V)>
+    where
+        C: Compare<Q, K> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = self.find_range(&range);
+        self.entries.drain(range)
+    }
+}
+
+impl<K, V, C: Compare<K>, S: SortedStorage> BinaryPairMapBy<K, V, C, S> {
+    fn collect_with_comparator<I: IntoIterator<Item = (K, V)>>(iter: I, compare: C) -> Self {
+        match Self::from_iter_with_policy(iter, DuplicatePolicy::LastWins, compare) {
+            Ok(map) => map,
+            Err(_) => unreachable!("only DuplicatePolicy::Error fails"),
+        }
+    }
+
+    fn from_iter_with_policy<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        policy: DuplicatePolicy<V>,
+        compare: C,
+    ) -> Result<Self, DuplicateKeyError<K>> {
+        let entries = S::Buffer::from_iter(iter);
+        let entries = policy.sort_and_resolve(entries, |a, b| compare.compare(a, b))?;
+        Ok(Self { entries, compare })
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        C: Compare<Q, K> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = self.find_range(&range);
+        Iter {
+            inner: self.entries[range].iter(),
+        }
+    }
+
+    pub fn range_mut<Q, R>(&mut self, range: R) -> IterMut<'_, K, V>
+    where
+        C: Compare<Q, K> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let range = self.find_range(&range);
+        IterMut {
+            inner: self.entries[range].iter_mut(),
+        }
+    }
+
+    /// Splits off the entries with keys greater than or equal to `key` into a new map.
+    pub fn split_off<Q: ?Sized>(&mut self, key: &Q) -> Self
+    where
+        C: Compare<Q, K> + Clone,
+    {
+        let index = search::lower_bound(&self.entries, key, |q, (k, _)| self.compare.compare(q, k));
+        Self {
+            entries: self.entries.split_off(index),
+            compare: self.compare.clone(),
+        }
+    }
+
+    /// Moves every entry of `other` into `self` with one merge, leaving `other` empty.
+    /// Entries of `other` replace entries of `self` with equal keys, as in `BTreeMap::append`.
+    pub fn append(&mut self, other: &mut Self) {
+        let len = self.entries.len() + other.entries.len();
+        let entries = std::mem::replace(&mut self.entries, S::Buffer::with_capacity(len));
+        let other_entries = std::mem::take(&mut other.entries);
+        merge::union_entries(
+            entries.into_iter(),
+            other_entries.into_iter(),
+            |a, b| self.compare.compare(a, b),
+            |key, value| self.entries.push((key, value)),
+        );
+    }
+}
+
+impl<K, V, C: Compare<K> + Default> BinaryPairMapBy<K, V, C> {
+    /// Collects `iter`, resolving entries with equal keys by `policy`.
+    /// Only [`DuplicatePolicy::Error`] can fail.
+    pub fn from_iter_with<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        policy: DuplicatePolicy<V>,
+    ) -> Result<Self, DuplicateKeyError<K>> {
+        Self::from_iter_with_policy(iter, policy, C::default())
+    }
+
+    /// Wraps `vec` after checking in linear time that its keys are sorted and deduplicated.
+    pub fn from_sorted_vec(vec: Vec<(K, V)>) -> Result<Self, FromSortedError> {
+        Self::from_sorted_vec_with_comparator(vec, C::default())
+    }
+
+    /// Collects `iter` after checking in linear time that its keys are sorted and deduplicated.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+    ) -> Result<Self, FromSortedError> {
+        Self::from_sorted_iter_with_comparator(iter, C::default())
+    }
+
+    /// Wraps `vec` without checking it.
+    ///
+    /// # Safety
+    ///
+    /// The keys of `vec` must be sorted and deduplicated by the comparator.
+    /// Every method of the map relies on this, and may otherwise return wrong results or panic.
+    pub unsafe fn from_sorted_unchecked(vec: Vec<(K, V)>) -> Self {
+        Self {
+            entries: vec,
+            compare: C::default(),
+        }
+    }
+}
+
+/// Lookups with [`Comparable`] queries, which follow the natural order of the keys, so these
+/// are only available on maps in that order. With another comparator, use `get` and the other
+/// lookups, which search with the comparator:
+///
+/// ```compile_fail
+/// use binary_tree_collections::compare::{Natural, Reverse};
+/// use binary_tree_collections::pair_map::BinaryPairMapBy;
+///
+/// let map = BinaryPairMapBy::from_iter_with_comparator([(1, 'a'), (2, 'b')], Reverse(Natural));
+/// map.get_by(&1);
+/// ```
+impl<K, V, S: SortedStorage> BinaryPairMapBy<K, V, Natural, S> {
+    /// Like `get`, but accepts any query that is [`Comparable`] with the keys.
+    /// The query must agree with the order of the map.
+    pub fn get_by<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<&V> {
+        let index = search::find(&self.entries, key, |q, (k, _)| q.compare(k)).ok()?;
+        Some(&self.entries[index].1)
+    }
+
+    pub fn get_mut_by<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> Option<&mut V> {
+        let index = search::find(&self.entries, key, |q, (k, _)| q.compare(k)).ok()?;
+        Some(&mut self.entries[index].1)
+    }
+
+    pub fn get_key_value_by<Q: ?Sized + Comparable<K>>(&self, key: &Q) -> Option<(&K, &V)> {
+        let index = search::find(&self.entries, key, |q, (k, _)| q.compare(k)).ok()?;
+        self.get_index(index)
+    }
+
+    /// Like `range`, but accepts any bounds that are [`Comparable`] with the keys and with each other.
+    pub fn range_by<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        Q: ?Sized + Comparable<K> + Comparable<Q>,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(&range, Comparable::<Q>::compare);
+        let range = search::range(&self.entries, &range, |q, (k, _)| q.compare(k));
+        Iter {
+            inner: self.entries[range].iter(),
+        }
+    }
+
+    pub fn remove_by<Q: ?Sized + Comparable<K>>(&mut self, key: &Q) -> Option<V> {
+        let index = search::find(&self.entries, key, |q, (k, _)| q.compare(k)).ok()?;
+        Some(self.entries.remove(index).1)
+    }
+}
+
+impl<K, V, C, S: SortedStorage> BinaryPairMapBy<K, V, C, S> {
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.entries.iter(),
+        }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            inner: self.entries.iter(),
+        }
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+
+    /// Iterates over the entries in order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Iterates over the entries in order, with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+
+    pub fn into_keys(self) -> Vec<K> {
+        self.entries.into_iter().map(|(k, _)| k).collect()
+    }
+
+    pub fn into_values(self) -> Vec<V> {
+        self.entries.into_iter().map(|(_, v)| v).collect()
+    }
+}
+
+impl<K, V, C, S: SortedStorage> BinaryPairMapBy<K, V, C, S> {
+    /// The position of `key` if it is present, or else where it would be inserted.
+    pub fn index_of<Q: ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        C: Compare<Q, K>,
+    {
+        self.find(key)
+    }
+
+    /// The number of keys less than `key`.
+    pub fn rank<Q: ?Sized>(&self, key: &Q) -> usize
+    where
+        C: Compare<Q, K>,
+    {
+        self.find(key).unwrap_or_else(|index| index)
+    }
+
+    /// The entry at position `index` in key order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        let (key, value) = self.entries.get(index)?;
+        Some((key, value))
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        let (key, value) = self.entries.get_mut(index)?;
+        Some((key, value))
+    }
+
+    /// Removes the entry at position `index`, shifting the ones after it down.
+    pub fn remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        Some(self.entries.remove(index))
+    }
+
+    /// The entry with the greatest key less than or equal to `key`.
+    pub fn floor<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let index = search::upper_bound(&self.entries, key, |q, (k, _)| self.compare.compare(q, k));
+        self.get_index(index.checked_sub(1)?)
+    }
+
+    /// The entry with the least key greater than or equal to `key`.
+    pub fn ceiling<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let index = search::lower_bound(&self.entries, key, |q, (k, _)| self.compare.compare(q, k));
+        self.get_index(index)
+    }
+
+    /// The entry with the greatest key strictly less than `key`.
+    pub fn lower<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let index = search::lower_bound(&self.entries, key, |q, (k, _)| self.compare.compare(q, k));
+        self.get_index(index.checked_sub(1)?)
+    }
+
+    /// The entry with the least key strictly greater than `key`.
+    pub fn higher<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let index = search::upper_bound(&self.entries, key, |q, (k, _)| self.compare.compare(q, k));
+        self.get_index(index)
+    }
+
+    /// The entry whose key is nearest to `key` by `distance`, which must grow with distance
+    /// in the order of the map. Ties go to the smaller key.
+    pub fn nearest<Q: ?Sized, D: PartialOrd>(
+        &self,
+        key: &Q,
+        distance: impl Fn(&Q, &K) -> D,
+    ) -> Option<(&K, &V)>
+    where
+        C: Compare<Q, K>,
+    {
+        let compare = |q: &Q, (k, _): &(K, V)| self.compare.compare(q, k);
+        let distance = |q: &Q, (k, _): &(K, V)| distance(q, k);
+        self.get_index(search::nearest(&self.entries, key, compare, distance)?)
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.get_index(self.entries.len().checked_sub(1)?)
+    }
+
+    /// Removes the entry with the smallest key. This shifts every other entry down.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.remove_index(0)
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.entries.pop()
+    }
+
+    /// Keeps only the entries for which `keep` returns true, in one pass.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        let mut entries = Retain::new(&mut self.entries, Buffer::truncate);
+        while let Some((key, value)) = entries.unread_mut().first_mut() {
+            if keep(key, value) {
+                entries.keep(1);
+            } else {
+                entries.skip(1);
+            }
+        }
+    }
+}
+
+impl<K, V> From<BinaryMap<K, V>> for BinaryPairMap<K, V> {
+    fn from(map: BinaryMap<K, V>) -> Self {
+        let (keys, values) = map.into_parts();
+        Self {
+            entries: keys.into_iter().zip(values).collect(),
+            compare: Natural,
+        }
+    }
+}
+
+impl<K, V> From<BinaryPairMap<K, V>> for BinaryMap<K, V> {
+    fn from(map: BinaryPairMap<K, V>) -> Self {
+        let (keys, values) = map.entries.into_iter().unzip();
+        BinaryMap::from_sorted_parts_unchecked(keys, values, Natural)
+    }
+}
+
+impl<K, V, C, S: SortedStorage> CollectionMut for BinaryPairMapBy<K, V, C, S> {
+    type ItemMut<'a>
+        = &'a mut V
+    where
+        Self: 'a;
+
+    covariant_item_mut!();
+}
+
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> GetMut<&'a Q>
+    for BinaryPairMapBy<K, V, C, S>
+{
+    fn get_mut(&mut self, key: &'a Q) -> Option<Self::ItemMut<'_>> {
+        let index = self.find(key).ok()?;
+        Some(&mut self.entries[index].1)
+    }
+}
+
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> Get<&'a Q>
+    for BinaryPairMapBy<K, V, C, S>
+{
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let index = self.find(key).ok()?;
+        Some(&self.entries[index].1)
+    }
+}
+
+impl<K, V, C: Compare<K>, S: SortedStorage> MapInsert<K> for BinaryPairMapBy<K, V, C, S> {
+    type Output = Option<V>;
+
+    fn insert(&mut self, key: K, value: Self::Item) -> Self::Output {
+        match self.find(&key) {
+            Ok(index) => Some(std::mem::replace(&mut self.entries[index].1, value)),
+            Err(index) => {
+                self.entries.insert(index, (key, value));
+                None
+            }
+        }
+    }
+}
+
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> Remove<&'a Q>
+    for BinaryPairMapBy<K, V, C, S>
+{
+    fn remove(&mut self, key: &'a Q) -> Option<Self::Item> {
+        let index = self.find(key).ok()?;
+        Some(self.entries.remove(index).1)
+    }
+}
+
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> GetKeyValue<&'a Q>
+    for BinaryPairMapBy<K, V, C, S>
+{
+    fn get_key_value(&self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemRef<'_>)> {
+        let index = self.find(key).ok()?;
+        self.get_index(index)
+    }
+}
+
+impl<'a, Q: ?Sized, K, V, C: Compare<Q, K>, S: SortedStorage> GetKeyValueMut<&'a Q>
+    for BinaryPairMapBy<K, V, C, S>
+{
+    fn get_key_value_mut(&mut self, key: &'a Q) -> Option<(Self::KeyRef<'_>, Self::ItemMut<'_>)> {
+        let index = self.find(key).ok()?;
+        self.get_index_mut(index)
+    }
+}
+
+impl<K, V, C, S: SortedStorage> KeyedRef for BinaryPairMapBy<K, V, C, S> {
+    type KeyRef<'a>
+        = &'a K
+    where
+        Self: 'a;
+
+    covariant_key_ref!();
+}
+
+impl<K, V, C, S: SortedStorage> Keyed for BinaryPairMapBy<K, V, C, S> {
+    type Key = K;
+}
+
+impl<K, V, C, S: SortedStorage> Clear for BinaryPairMapBy<K, V, C, S> {
+    fn clear(&mut self) {
+        self.entries.truncate(0);
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, C, S: SortedStorage> PartialEq for BinaryPairMapBy<K, V, C, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries[..] == other.entries[..]
+    }
+}
+
+impl<K: Eq, V: Eq, C, S: SortedStorage> Eq for BinaryPairMapBy<K, V, C, S> {}
+
+impl<K: PartialOrd, V: PartialOrd, C, S: SortedStorage> PartialOrd for BinaryPairMapBy<K, V, C, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.entries[..].partial_cmp(&other.entries[..])
+    }
+}
+
+impl<K: Ord, V: Ord, C, S: SortedStorage> Ord for BinaryPairMapBy<K, V, C, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.entries[..].cmp(&other.entries[..])
+    }
+}
+
+impl<K, V, C: Compare<K> + Default, S: SortedStorage> FromIterator<(K, V)>
+    for BinaryPairMapBy<K, V, C, S>
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::collect_with_comparator(iter, C::default())
+    }
+}
+
+impl<K, V, C: Compare<K>, S: SortedStorage> Extend<(K, V)> for BinaryPairMapBy<K, V, C, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+        // while the entries are inline, they go in one at a time so that small maps never allocate
+        while self.entries.as_vec_mut().is_none() {
+            let Some((key, value)) = iter.next() else {
+                return;
+            };
+            self.insert(key, value);
+        }
+        let compare = &self.compare;
+        let mut new_entries = Vec::new();
+        let indices = merge::place_batch(
+            &mut self.entries,
+            |(k, _)| k,
+            Vec::from_iter(iter),
+            |a, b| compare.compare(a, b),
+            |(_, v), _, value| *v = value,
+            |key, value| new_entries.push((key, value)),
+        );
+
+        self.entries
+            .with_vec(|entries| merge::insert_at(entries, &indices, new_entries));
+    }
+}
+
+impl<'a, K: Copy, V: Copy, C: Compare<K>, S: SortedStorage> Extend<(&'a K, &'a V)>
+    for BinaryPairMapBy<K, V, C, S>
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(k, v)| (*k, *v)))
+    }
+}
+
+impl<K, V, C, S: SortedStorage> Reserve for BinaryPairMapBy<K, V, C, S> {
+    fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+    }
+}
+
+impl<K, V, C: Default, S: SortedStorage> WithCapacity for BinaryPairMapBy<K, V, C, S> {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: S::Buffer::with_capacity(capacity),
+            compare: C::default(),
+        }
+    }
+}
+
+impl<K, V, C, S: SortedStorage> Capacity for BinaryPairMapBy<K, V, C, S> {
+    fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+}
+
+impl<K, V, C, S: SortedStorage> cc_traits::Iter for BinaryPairMapBy<K, V, C, S> {
+    type Iter<'a>
+        = Values<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.values()
+    }
+}
+
+impl<K, V, C, S: SortedStorage> CollectionRef for BinaryPairMapBy<K, V, C, S> {
+    type ItemRef<'a>
+        = &'a V
+    where
+        Self: 'a;
+
+    covariant_item_ref!();
+}
+
+impl<K, V, C, S: SortedStorage> Collection for BinaryPairMapBy<K, V, C, S> {
+    type Item = V;
+}
+
+impl<K, V, C, S: SortedStorage> SimpleCollectionRef for BinaryPairMapBy<K, V, C, S> {
+    simple_collection_ref!();
+}
+
+impl<K, V, C, S: SortedStorage> SimpleCollectionMut for BinaryPairMapBy<K, V, C, S> {
+    simple_collection_mut!();
+}
+
+impl<K, V, C, S: SortedStorage> SimpleKeyedRef for BinaryPairMapBy<K, V, C, S> {
+    simple_keyed_ref!();
+}
+
+impl<K, V, C, S: SortedStorage> MapIter for BinaryPairMapBy<K, V, C, S> {
+    type Iter<'a>
+        = Iter<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+impl<K, V, C, S: SortedStorage> MapIterMut for BinaryPairMapBy<K, V, C, S> {
+    type IterMut<'a>
+        = IterMut<'a, K, V>
+    where
+        Self: 'a;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, C, S: SortedStorage> Len for BinaryPairMapBy<K, V, C, S> {
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl<K, V, C, S: SortedStorage> IntoIterator for BinaryPairMapBy<K, V, C, S> {
+    type Item = (K, V);
+    type IntoIter = <S::Buffer<(K, V)> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, K, V, C, S: SortedStorage> IntoIterator for &'a BinaryPairMapBy<K, V, C, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, C, S: SortedStorage> IntoIterator for &'a mut BinaryPairMapBy<K, V, C, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::ops::Bound;
+
+    use crate::pair_map::*;
+
+    #[test]
+    pub fn insert_get_remove() {
+        let mut map = BinaryPairMap::from_iter([(3, 'c'), (1, 'a'), (2, 'b')]);
+        assert_eq!(map.as_slice(), &[(1, 'a'), (2, 'b'), (3, 'c')]);
+        assert_eq!(map.insert(2, 'B'), Some('b'));
+        assert_eq!(map.insert(0, 'z'), None);
+        assert_eq!(map.get(&2), Some(&'B'));
+        assert_eq!(map.get_key_value(&3), Some((&3, &'c')));
+        *map.get_mut(&1).unwrap() = 'A';
+        assert_eq!(map.remove(&0), Some('z'));
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.as_slice(), &[(1, 'A'), (2, 'B'), (3, 'c')]);
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    pub fn iterators() {
+        let mut map = BinaryPairMap::from_iter((0..6).map(|k| (k, k * 10)));
+        for (_, v) in map.iter_mut() {
+            *v += 1;
+        }
+        map.values_mut().for_each(|v| *v *= 2);
+        assert!(map.keys().copied().eq(0..6));
+        assert!(map
+            .values()
+            .rev()
+            .copied()
+            .eq((0..6).rev().map(|k| (k * 10 + 1) * 2)));
+        assert_eq!(
+            map.range(2..4).collect::<Vec<_>>(),
+            vec![(&2, &42), (&3, &62)]
+        );
+        assert_eq!(map.range((Bound::Excluded(3), Bound::Unbounded)).len(), 2);
+        map.range_mut(..1).for_each(|(_, v)| *v = 0);
+        assert_eq!(map.first(), Some((&0, &0)));
+        assert_eq!(map.clone().into_iter().last(), Some((5, 102)));
+    }
+
+    #[test]
+    pub fn entry() {
+        let mut map: BinaryPairMap<&str, u32> = BinaryPairMap::default();
+        *map.entry("b").or_insert(0) += 1;
+        *map.entry("a").or_default() += 5;
+        *map.entry("b").or_insert(0) += 1;
+        assert_eq!(map.as_slice(), &[("a", 5), ("b", 2)]);
+        match map.entry("a") {
+            Entry::Occupied(entry) => assert_eq!(entry.remove_entry(), ("a", 5)),
+            Entry::Vacant(_) => unreachable!(),
+        }
+        assert_eq!(map.as_slice(), &[("b", 2)]);
+    }
+
+    #[test]
+    pub fn extend_matches_insert() {
+        let mut extended = BinaryPairMap::from_iter((0..20).step_by(2).map(|k| (k, 0)));
+        let mut inserted = extended.clone();
+        let batch = [(5, 1), (4, 1), (21, 1), (5, 2), (-1, 1)];
+        extended.extend(batch);
+        for (k, v) in batch {
+            inserted.insert(k, v);
+        }
+        assert_eq!(extended, inserted);
+    }
+
+    #[test]
+    pub fn constructors() {
+        use crate::error::FromSortedError;
+
+        let map = BinaryPairMap::from_iter_with(
+            [(1, 1), (2, 2), (1, 3)],
+            DuplicatePolicy::Merge(|a, b| a + b),
+        );
+        assert_eq!(map.unwrap().as_slice(), &[(1, 4), (2, 2)]);
+        assert_eq!(
+            BinaryPairMap::from_sorted_vec(vec![(1, 'a'), (1, 'b')]),
+            Err(FromSortedError::Duplicate { index: 1 })
+        );
+        let map = BinaryPairMap::from_sorted_iter([(1, 'a'), (2, 'b')]).unwrap();
+        let binary = BinaryMap::from(map.clone());
+        assert_eq!(binary.keys(), &vec![1, 2]);
+        assert_eq!(BinaryPairMap::from(binary), map);
+    }
+
+    #[test]
+    pub fn positional() {
+        let mut map = BinaryPairMap::from_iter([(10, 'a'), (20, 'b'), (30, 'c')]);
+        assert_eq!(map.index_of(&20), Ok(1));
+        assert_eq!(map.rank(&25), 2);
+        assert_eq!(map.floor(&25), Some((&20, &'b')));
+        assert_eq!(map.higher(&30), None);
+        assert_eq!(
+            map.nearest(&26, |a: &i32, b| a.abs_diff(*b)),
+            Some((&30, &'c'))
+        );
+        assert_eq!(map.remove_index(1), Some((20, 'b')));
+        assert_eq!(map.pop_first(), Some((10, 'a')));
+        assert_eq!(map.pop_last(), Some((30, 'c')));
+        assert_eq!(map.pop_last(), None);
+    }
+
+    #[test]
+    pub fn bulk_mutation() {
+        let mut map = BinaryPairMap::from_iter((0..10).map(|k| (k, k)));
+        map.retain(|k, _| k % 3 != 0);
+        let odd: Vec<_> = map.extract_if(|k, _| k % 2 == 1).collect();
+        assert_eq!(odd, vec![(1, 1), (5, 5), (7, 7)]);
+        assert_eq!(map.drain_range(3..5).collect::<Vec<_>>(), vec![(4, 4)]);
+        let high = map.split_off(&8);
+        assert_eq!(map.as_slice(), &[(2, 2)]);
+        assert_eq!(high.as_slice(), &[(8, 8)]);
+
+        let mut other = BinaryPairMap::from_iter([(1, -1), (2, -2)]);
+        map.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(map.as_slice(), &[(1, -1), (2, -2)]);
+        assert_eq!(map.drain().count(), 2);
+    }
+
+    #[test]
+    pub fn small_storage() {
+        let mut map: SmallBinaryPairMap<u32, char, 4> = [(2, 'b'), (1, 'a')].into_iter().collect();
+        map.extend([(3, 'c'), (1, 'A')]);
+        map.retain(|k, _| *k != 2);
+        assert_eq!(map.as_slice(), &[(1, 'A'), (3, 'c')]);
+
+        map.extend((4..8).map(|k| (k, 'x')));
+        let mut other = SmallBinaryPairMap::from_iter([(0, 'z'), (3, 'C')]);
+        map.append(&mut other);
+        assert!(other.is_empty());
+        let expected = [(0, 'z'), (1, 'A'), (3, 'C')]
+            .into_iter()
+            .chain((4..8).map(|k| (k, 'x')));
+        assert_eq!(map.into_storage(), BinaryPairMap::from_iter(expected));
+    }
+
+    #[test]
+    pub fn reverse_comparator_lookups() {
+        use crate::compare::Reverse;
+
+        let mut map = BinaryPairMapBy::from_iter_with_comparator(
+            [(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')],
+            Reverse(Natural),
+        );
+        assert_eq!(map.get(&4), Some(&'d'));
+        assert_eq!(map.get_key_value(&1), Some((&1, &'a')));
+        assert_eq!(
+            map.range((Bound::Included(4), Bound::Included(2)))
+                .map(|x| *x.1)
+                .collect::<Vec<_>>(),
+            vec!['d', 'c', 'b']
+        );
+        assert_eq!(map.remove(&3), Some('c'));
+        assert_eq!(map.as_slice(), &[(4, 'd'), (2, 'b'), (1, 'a')]);
+    }
+}
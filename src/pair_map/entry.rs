@@ -0,0 +1,170 @@
+use std::{fmt, mem};
+
+use crate::compare::{Compare, Natural};
+use crate::pair_map::BinaryPairMapBy;
+use crate::storage::{Buffer, SortedStorage, VecStore};
+
+/// A view into a single entry of a [`BinaryPairMapBy`], which may be vacant or occupied.
+/// Returned by [`BinaryPairMapBy::entry`].
+#[derive(Debug)]
+pub enum Entry<'a, K, V, C = Natural, S: SortedStorage = VecStore> {
+    Vacant(VacantEntry<'a, K, V, C, S>),
+    Occupied(OccupiedEntry<'a, K, V, C, S>),
+}
+
+/// A view into a vacant entry. It remembers where the key belongs.
+pub struct VacantEntry<'a, K, V, C = Natural, S: SortedStorage = VecStore> {
+    map: &'a mut BinaryPairMapBy<K, V, C, S>,
+    key: K,
+    index: usize,
+}
+
+/// A view into an occupied entry. It remembers where the key was found.
+pub struct OccupiedEntry<'a, K, V, C = Natural, S: SortedStorage = VecStore> {
+    map: &'a mut BinaryPairMapBy<K, V, C, S>,
+    index: usize,
+}
+
+impl<K, V, C: Compare<K>, S: SortedStorage> BinaryPairMapBy<K, V, C, S> {
+    /// Gets the entry for `key`, searching for it only once.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, C, S> {
+        match self.find(&key) {
+            Ok(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            Err(index) => Entry::Vacant(VacantEntry {
+                map: self,
+                key,
+                index,
+            }),
+        }
+    }
+}
+
+impl<'a, K, V, C, S: SortedStorage> Entry<'a, K, V, C, S> {
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default()),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    /// Like [`Entry::or_insert_with`], but the default function is given the key.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => {
+                let value = default(&entry.key);
+                entry.insert(value)
+            }
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
+        }
+    }
+
+    /// Modifies the value if the entry is occupied.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Sets the value of the entry, returning an occupied entry.
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, C, S> {
+        match self {
+            Entry::Vacant(entry) => entry.insert_entry(value),
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+                entry
+            }
+        }
+    }
+}
+
+impl<'a, K, V: Default, C, S: SortedStorage> Entry<'a, K, V, C, S> {
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V, C, S: SortedStorage> VacantEntry<'a, K, V, C, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts the key and value at the remembered position.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.insert_entry(value).into_mut()
+    }
+
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, C, S> {
+        self.map.entries.insert(self.index, (self.key, value));
+        OccupiedEntry {
+            map: self.map,
+            index: self.index,
+        }
+    }
+}
+
+impl<'a, K, V, C, S: SortedStorage> OccupiedEntry<'a, K, V, C, S> {
+    pub fn key(&self) -> &K {
+        &self.map.entries[self.index].0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.map.entries[self.index].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Replaces the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.entries.remove(self.index)
+    }
+}
+
+impl<K: fmt::Debug, V, C, S: SortedStorage> fmt::Debug for VacantEntry<'_, K, V, C, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.key()).finish()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, C, S: SortedStorage> fmt::Debug
+    for OccupiedEntry<'_, K, V, C, S>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
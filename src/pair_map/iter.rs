@@ -0,0 +1,99 @@
+use std::iter::FusedIterator;
+use std::slice;
+
+/// Iterator over the entries of a [`BinaryPairMap`](crate::pair_map::BinaryPairMap),
+/// returned by `iter` and `range`.
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    pub(super) inner: slice::Iter<'a, (K, V)>,
+}
+
+/// Mutable iterator over the entries of a [`BinaryPairMap`](crate::pair_map::BinaryPairMap),
+/// returned by `iter_mut` and `range_mut`.
+#[derive(Debug)]
+pub struct IterMut<'a, K, V> {
+    pub(super) inner: slice::IterMut<'a, (K, V)>,
+}
+
+/// Iterator over the keys of a [`BinaryPairMap`](crate::pair_map::BinaryPairMap), returned by `keys`.
+#[derive(Debug)]
+pub struct Keys<'a, K, V> {
+    pub(super) inner: slice::Iter<'a, (K, V)>,
+}
+
+/// Iterator over the values of a [`BinaryPairMap`](crate::pair_map::BinaryPairMap), returned by `values`.
+#[derive(Debug)]
+pub struct Values<'a, K, V> {
+    pub(super) inner: slice::Iter<'a, (K, V)>,
+}
+
+/// Mutable iterator over the values of a [`BinaryPairMap`](crate::pair_map::BinaryPairMap),
+/// returned by `values_mut`.
+#[derive(Debug)]
+pub struct ValuesMut<'a, K, V> {
+    pub(super) inner: slice::IterMut<'a, (K, V)>,
+}
+
+/// Forwards the iterator traits to the `inner` iterator, projecting each entry with `$project`.
+macro_rules! project_iterator {
+    ($name:ident<$($param:tt),*>, $item:ty, $project:expr) => {
+        impl<$($param),*> Iterator for $name<$($param),*> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.inner.next().map($project)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+
+            fn nth(&mut self, n: usize) -> Option<Self::Item> {
+                self.inner.nth(n).map($project)
+            }
+        }
+
+        impl<$($param),*> DoubleEndedIterator for $name<$($param),*> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.inner.next_back().map($project)
+            }
+
+            fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+                self.inner.nth_back(n).map($project)
+            }
+        }
+
+        impl<$($param),*> ExactSizeIterator for $name<$($param),*> {}
+        impl<$($param),*> FusedIterator for $name<$($param),*> {}
+    };
+}
+
+project_iterator!(Iter<'a, K, V>, (&'a K, &'a V), |(k, v)| (k, v));
+project_iterator!(IterMut<'a, K, V>, (&'a K, &'a mut V), |(k, v)| (&*k, v));
+project_iterator!(Keys<'a, K, V>, &'a K, |(k, _)| k);
+project_iterator!(Values<'a, K, V>, &'a V, |(_, v)| v);
+project_iterator!(ValuesMut<'a, K, V>, &'a mut V, |(_, v)| v);
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Searches over sorted slices, shared by the sets and maps.
+
+use core::cmp::Ordering;
+use core::ops::{Bound, Range, RangeBounds};
+
+#[cfg(feature = "std")]
+use crate::error::FromSortedError;
+
+/// Binary searches the sorted `slice` for an element that compares equal to `key`.
+pub(crate) fn find<T, Q: ?Sized>(
+    slice: &[T],
+    key: &Q,
+    compare: impl Fn(&Q, &T) -> Ordering,
+) -> Result<usize, usize> {
+    slice.binary_search_by(|x| compare(key, x).reverse())
+}
+
+/// The index of the first element of the sorted `slice` that is not less than `key`.
+pub(crate) fn lower_bound<T, Q: ?Sized>(
+    slice: &[T],
+    key: &Q,
+    compare: impl Fn(&Q, &T) -> Ordering,
+) -> usize {
+    slice.partition_point(|x| compare(key, x).is_gt())
+}
+
+/// The index of the first element of the sorted `slice` that is greater than `key`.
+pub(crate) fn upper_bound<T, Q: ?Sized>(
+    slice: &[T],
+    key: &Q,
+    compare: impl Fn(&Q, &T) -> Ordering,
+) -> usize {
+    slice.partition_point(|x| compare(key, x).is_ge())
+}
+
+/// The index of the element of the sorted `slice` nearest to `key` by `distance`.
+/// Only the neighbours of `key` in order are measured, and ties go to the smaller one.
+#[cfg(feature = "std")]
+pub(crate) fn nearest<T, Q: ?Sized, D: PartialOrd>(
+    slice: &[T],
+    key: &Q,
+    compare: impl Fn(&Q, &T) -> Ordering,
+    distance: impl Fn(&Q, &T) -> D,
+) -> Option<usize> {
+    let above = lower_bound(slice, key, compare);
+    match (above.checked_sub(1), slice.get(above)) {
+        (Some(below), Some(x)) if distance(key, x) < distance(key, &slice[below]) => Some(above),
+        (Some(below), _) => Some(below),
+        (None, Some(_)) => Some(above),
+        (None, None) => None,
+    }
+}
+
+/// Checks that `previous` comes strictly before the element at `index`.
+#[cfg(feature = "std")]
+pub(crate) fn check_next<T: ?Sized>(
+    previous: &T,
+    next: &T,
+    index: usize,
+    compare: impl Fn(&T, &T) -> Ordering,
+) -> Result<(), FromSortedError> {
+    match compare(previous, next) {
+        Ordering::Less => Ok(()),
+        Ordering::Equal => Err(FromSortedError::Duplicate { index }),
+        Ordering::Greater => Err(FromSortedError::Unsorted { index }),
+    }
+}
+
+/// Checks that `slice` is strictly increasing.
+#[cfg(feature = "std")]
+pub(crate) fn check_sorted<T>(
+    slice: &[T],
+    compare: impl Fn(&T, &T) -> Ordering,
+) -> Result<(), FromSortedError> {
+    for (i, pair) in slice.windows(2).enumerate() {
+        check_next(&pair[0], &pair[1], i + 1, &compare)?;
+    }
+    Ok(())
+}
+
+/// Panics if the range starts after it ends, or starts and ends at the same excluded bound,
+/// like `BTreeMap::range`.
+pub(crate) fn check_bounds<Q: ?Sized>(
+    range: &impl RangeBounds<Q>,
+    compare: impl Fn(&Q, &Q) -> Ordering,
+) {
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Excluded(start), Bound::Excluded(end)) if compare(start, end).is_eq() => {
+            panic!("range start and end are equal and excluded")
+        }
+        (
+            Bound::Included(start) | Bound::Excluded(start),
+            Bound::Included(end) | Bound::Excluded(end),
+        ) if compare(start, end).is_gt() => panic!("range start is greater than range end"),
+        _ => {}
+    }
+}
+
+/// Returns the indices of the elements of the sorted `slice` that lie within `range`.
+///
+/// Without [`check_bounds`], a backwards range only panics if there are elements between its bounds.
+pub(crate) fn range<T, Q: ?Sized>(
+    slice: &[T],
+    range: &impl RangeBounds<Q>,
+    compare: impl Fn(&Q, &T) -> Ordering,
+) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(k) => lower_bound(slice, k, &compare),
+        Bound::Excluded(k) => upper_bound(slice, k, &compare),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(k) => upper_bound(slice, k, &compare),
+        Bound::Excluded(k) => lower_bound(slice, k, &compare),
+        Bound::Unbounded => slice.len(),
+    };
+    assert!(start <= end, "range start is greater than range end");
+    start..end
+}
@@ -1,13 +1,142 @@
 use cc_traits::*;
+use std::cmp::Ordering;
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, RangeBounds, Sub, SubAssign,
+};
 use std::vec::Vec;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
-pub struct BinarySet<T>(Vec<T>);
+pub use crate::algebra::{Difference, Intersection, SymmetricDifference, Union};
+use crate::comparable::Comparable;
+use crate::compare::{Compare, Natural};
+use crate::error::FromSortedError;
+use crate::storage::{Buffer, SmallStore, SortedStorage, VecStore};
+use crate::{merge, search};
+
+/// A set stored as a sorted buffer, ordered by the comparator `C`.
+/// The storage `S` picks the buffer, which is a `Vec` by default.
+///
+/// Lookups take an element by reference, as any `&Q` that `C` compares with `T`. There is no
+/// by-value `Get<T>`, since it would overlap with the `&Q` impl when `T` is itself a reference,
+/// so code that passed elements by value must pass `&element` instead.
+#[derive(Debug, Clone, Default)]
+pub struct BinarySetBy<T, C, S: SortedStorage = VecStore>(S::Buffer<T>, C);
+
+/// A set stored as a sorted `Vec`, in the natural order of its elements.
+pub type BinarySet<T> = BinarySetBy<T, Natural, VecStore>;
+
+/// A set that keeps up to `N` elements inline, in the natural order of its elements.
+pub type SmallBinarySet<T, const N: usize> = BinarySetBy<T, Natural, SmallStore<N>>;
+
+/// Batches at most this long, and this many times smaller than the set,
+/// are inserted one at a time when extending.
+const INSERTS_MAX_BATCH: usize = 4;
+const INSERTS_MIN_RATIO: usize = 16;
+
+/// How [`BinarySetBy::extend_with_strategy`] adds a batch of elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtendStrategy {
+    /// Chooses from the size of the batch and whether it overlaps the set, prepends or appends.
+    #[default]
+    Auto,
+    /// Appends the batch, then sorts and deduplicates everything.
+    Sort,
+    /// Inserts each element with a binary search. Best for a handful of elements.
+    Inserts,
+    /// Sorts the batch and rotates each element into place. Best for a batch that prepends.
+    Rotates,
+    /// Sorts the batch and merges it in from the back in linear time.
+    Merge,
+}
+
+impl<T, C> BinarySetBy<T, C> {
+    pub const fn with_comparator(compare: C) -> Self {
+        Self(Vec::new(), compare)
+    }
+
+    /// Wraps a vector that is already sorted and deduplicated by `compare`.
+    pub(crate) fn from_sorted_vec_unchecked(vec: Vec<T>, compare: C) -> Self {
+        Self(vec, compare)
+    }
+
+    /// Removes the elements within `range` and iterates over them in ascending order.
+    pub fn drain_range<Q, R>(&mut self, range: R) -> std::vec::Drain<'_, T>
+    where
+        C: Compare<Q, T> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(&range, |a, b| self.1.compare(a, b));
+        let range = search::range(&self.0, &range, |q, x| self.1.compare(q, x));
+        self.0.drain(range)
+    }
+
+    /// Removes every element and iterates over them in ascending order.
+    pub fn drain(&mut self) -> std::vec::Drain<'_, T> {
+        self.0.drain(..)
+    }
+
+    /// Removes the elements for which `extract` returns true as the iterator reaches them.
+    /// Elements it does not reach are kept.
+    pub fn extract_if<'a, F: FnMut(&T) -> bool + 'a>(
+        &'a mut self,
+        mut extract: F,
+    ) -> impl Iterator<Item = T> + 'a {
+        self.0.extract_if(.., move |x| extract(x))
+    }
+}
+
+impl<T, C, S: SortedStorage> BinarySetBy<T, C, S> {
+    pub const fn comparator(&self) -> &C {
+        &self.1
+    }
+
+    /// The buffer the elements are stored in.
+    pub fn buffer(&self) -> &S::Buffer<T> {
+        &self.0
+    }
+
+    /// Moves the elements into the storage `S2`.
+    pub fn into_storage<S2: SortedStorage>(self) -> BinarySetBy<T, C, S2> {
+        let vec: Vec<T> = self.0.into();
+        BinarySetBy(vec.into(), self.1)
+    }
+
+    fn find<Q: ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        C: Compare<Q, T>,
+    {
+        search::find(&self.0, key, |q, x| self.1.compare(q, x))
+    }
+}
+
+impl<T, C: Compare<T>> BinarySetBy<T, C> {
+    pub fn from_iter_with_comparator<I: IntoIterator<Item = T>>(iter: I, compare: C) -> Self {
+        Self::collect_with_comparator(iter, compare)
+    }
+
+    /// Wraps `vec` after checking in linear time that it is sorted and deduplicated by `compare`.
+    pub fn from_sorted_vec_with_comparator(
+        vec: Vec<T>,
+        compare: C,
+    ) -> Result<Self, FromSortedError> {
+        search::check_sorted(&vec, |a, b| compare.compare(a, b))?;
+        Ok(Self(vec, compare))
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> BinarySetBy<T, C, S> {
+    /// Sorts and deduplicates `iter` in the set's own buffer rather than a separate `Vec`.
+    fn collect_with_comparator<I: IntoIterator<Item = T>>(iter: I, compare: C) -> Self {
+        let mut buffer = S::Buffer::from_iter(iter);
+        buffer.sort_by(|a, b| compare.compare(a, b));
+        buffer.dedup_by(|a, b| compare.compare(a, b).is_eq());
+
+        Self(buffer, compare)
+    }
 
-impl<T: Ord + PartialOrd + Eq + PartialEq> BinarySet<T> {
     pub fn extend_with_inserts<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for item in iter {
-            match self.0.binary_search(&item) {
+            match self.find(&item) {
                 Ok(_) => {
                     //already exists - do nothing
                 }
@@ -16,12 +145,134 @@ impl<T: Ord + PartialOrd + Eq + PartialEq> BinarySet<T> {
         }
     }
 
+    /// Appends the elements of `iter` and sorts and deduplicates the whole vector.
+    pub fn extend_with_sort<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.sort_all();
+    }
+
+    /// Appends the elements of `iter`, sorts and deduplicates just them, then merges them in
+    /// from the back, moving each element of the set at most once.
+    /// Elements already in the set win over equal new ones, and among equal new elements the first wins.
+    pub fn extend_with_merge<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+        self.insert_inline(&mut iter, false);
+        let compare = &self.1;
+        if let Some(vec) = self.0.as_vec_mut() {
+            let start = vec.len();
+            vec.extend(iter);
+            merge::union_tail(vec, start, |a, b| compare.compare(a, b));
+        }
+    }
+
+    /// Adds the elements of `iter` using `strategy`.
+    /// [`ExtendStrategy::Auto`] is what [`Extend::extend`] uses.
+    /// A small set takes elements one at a time until it spills, whatever the strategy.
+    /// The rest are appended to the set's own buffer and put in place from there.
+    pub fn extend_with_strategy<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+        strategy: ExtendStrategy,
+    ) {
+        let mut iter = iter.into_iter();
+        self.insert_inline(&mut iter, false);
+        let start = self.0.len();
+        self.0.extend(iter);
+        let strategy = match strategy {
+            ExtendStrategy::Auto => self.choose_strategy(start),
+            strategy => strategy,
+        };
+        match strategy {
+            ExtendStrategy::Inserts => self.insert_tail(start),
+            ExtendStrategy::Rotates => self.rotate_tail(start),
+            ExtendStrategy::Sort => self.sort_all(),
+            ExtendStrategy::Auto | ExtendStrategy::Merge => self.merge_tail(start),
+        }
+    }
+
+    /// Inserts elements of `iter` one at a time for as long as the buffer is not a `Vec`, so that
+    /// batches that fit in a small set never allocate. Elements equal to one in the set replace it
+    /// if `replace` is set, and are dropped otherwise.
+    fn insert_inline(&mut self, iter: &mut impl Iterator<Item = T>, replace: bool) {
+        while self.0.as_vec_mut().is_none() {
+            let Some(element) = iter.next() else {
+                return;
+            };
+            match self.find(&element) {
+                Ok(index) if replace => self.0[index] = element,
+                Ok(_) => {}
+                Err(index) => self.0.insert(index, element),
+            }
+        }
+    }
+
+    /// Picks a strategy for adding the elements from `start` on, from how many there are and
+    /// where they land among the sorted elements before `start`.
+    fn choose_strategy(&self, start: usize) -> ExtendStrategy {
+        let compare = &self.1;
+        let (set, batch) = self.0.split_at(start);
+        let Some(first) = set.first() else {
+            return ExtendStrategy::Sort;
+        };
+        let few = batch.len() <= INSERTS_MAX_BATCH;
+        if few && batch.len() * INSERTS_MIN_RATIO <= set.len() {
+            return ExtendStrategy::Inserts;
+        }
+        if batch.iter().all(|x| compare.compare(x, first).is_lt()) {
+            // one rotation moves the old elements past the new ones
+            ExtendStrategy::Rotates
+        } else {
+            // an appended batch merges without moving the set, an overlapping one in linear time
+            ExtendStrategy::Merge
+        }
+    }
+
+    /// Moves each element from `start` on into place with a binary search, dropping those
+    /// equal to one already placed.
+    fn insert_tail(&mut self, start: usize) {
+        let compare = &self.1;
+        let mut sorted = start;
+        while sorted < self.0.len() {
+            let (set, tail) = self.0.split_at(sorted);
+            match search::find(set, &tail[0], |q, x| compare.compare(q, x)) {
+                Ok(_) => drop(self.0.remove(sorted)),
+                Err(index) => {
+                    self.0[index..=sorted].rotate_right(1);
+                    sorted += 1;
+                }
+            }
+        }
+    }
+
+    /// Merges the elements from `start` on into the sorted elements before it.
+    fn merge_tail(&mut self, start: usize) {
+        let compare = &self.1;
+        match self.0.as_vec_mut() {
+            Some(vec) => merge::union_tail(vec, start, |a, b| compare.compare(a, b)),
+            // an inline buffer has taken every element one at a time already
+            None => debug_assert_eq!(start, self.0.len()),
+        }
+    }
+
+    /// Sorts and deduplicates every element.
+    fn sort_all(&mut self) {
+        let compare = &self.1;
+        self.0.sort_by(|a, b| compare.compare(a, b));
+        self.0.dedup_by(|a, b| compare.compare(a, b).is_eq());
+    }
+
     pub fn extend_with_rotates<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        let mut before_new_elements = self.0.len();
+        let start = self.0.len();
         self.0.extend(iter);
+        self.rotate_tail(start);
+    }
 
+    /// Sorts the elements from `start` on and rotates each into place, last first.
+    fn rotate_tail(&mut self, start: usize) {
+        let compare = &self.1;
+        let mut before_new_elements = start;
         let (old_slice, new_slice) = self.0.split_at_mut(before_new_elements);
-        new_slice.sort();
+        new_slice.sort_by(|a, b| compare.compare(a, b));
         let Some(first) = new_slice.first() else{return;};
 
         let mut duplicates = false;
@@ -29,7 +280,7 @@ impl<T: Ord + PartialOrd + Eq + PartialEq> BinarySet<T> {
         {
             let mut current = first;
             for x in new_slice.iter().skip(1) {
-                if x == current {
+                if compare.compare(x, current).is_eq() {
                     duplicates = true;
                     break;
                 }
@@ -37,7 +288,7 @@ impl<T: Ord + PartialOrd + Eq + PartialEq> BinarySet<T> {
             }
         }
         let mut new_elements = new_slice.len();
-        let first_index = match old_slice.binary_search(first) {
+        let first_index = match old_slice.binary_search_by(|x| compare.compare(x, first)) {
             Ok(i) => {
                 duplicates = true;
                 i
@@ -51,12 +302,12 @@ impl<T: Ord + PartialOrd + Eq + PartialEq> BinarySet<T> {
             let index_to_insert = if first_index >= before.len() {
                 before.len()
             } else {
-                match before[first_index..].binary_search(to_insert) {
+                match before[first_index..].binary_search_by(|x| compare.compare(x, to_insert)) {
                     Ok(i) => {
                         duplicates = true;
-                        i
+                        first_index + i
                     }
-                    Err(i) => i,
+                    Err(i) => first_index + i,
                 }
             };
 
@@ -71,68 +322,509 @@ impl<T: Ord + PartialOrd + Eq + PartialEq> BinarySet<T> {
         }
 
         if duplicates {
-            self.0.dedup()
+            self.0.dedup_by(|a, b| compare.compare(a, b).is_eq())
+        }
+    }
+
+    /// Visits the elements within `range`, in ascending order.
+    pub fn range<Q, R>(&self, range: R) -> core::slice::Iter<'_, T>
+    where
+        C: Compare<Q, T> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.range_slice(range).iter()
+    }
+
+    /// Returns the sorted sub-slice of elements within `range`.
+    pub fn range_slice<Q, R>(&self, range: R) -> &[T]
+    where
+        C: Compare<Q, T> + Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(&range, |a, b| self.1.compare(a, b));
+        &self.0[search::range(&self.0, &range, |q, x| self.1.compare(q, x))]
+    }
+
+    /// Splits off the elements greater than or equal to `key` into a new set.
+    pub fn split_off<Q: ?Sized>(&mut self, key: &Q) -> Self
+    where
+        C: Compare<Q, T> + Clone,
+    {
+        let index = search::lower_bound(&self.0, key, |q, x| self.1.compare(q, x));
+        Self(self.0.split_off(index), self.1.clone())
+    }
+
+    /// Moves every element of `other` into `self` with one merge, leaving `other` empty.
+    /// Elements of `other` replace equal elements of `self`, as in `BTreeSet::append`.
+    pub fn append(&mut self, other: &mut Self) {
+        let mut other = std::mem::take(&mut other.0).into_iter();
+        self.insert_inline(&mut other, true);
+        if self.0.as_vec_mut().is_some() {
+            let compare = &self.1;
+            let mut merged = Vec::from_iter(other);
+            merge::union(&mut merged, std::mem::take(&mut self.0).into(), |a, b| {
+                compare.compare(a, b)
+            });
+            self.0 = merged.into();
         }
     }
 }
 
-impl<T: Ord + PartialOrd + Eq + PartialEq> Extend<T> for BinarySet<T> {
+impl<T, C: Compare<T> + Default> BinarySetBy<T, C> {
+    /// Wraps `vec` after checking in linear time that it is sorted and deduplicated.
+    pub fn from_sorted_vec(vec: Vec<T>) -> Result<Self, FromSortedError> {
+        Self::from_sorted_vec_with_comparator(vec, C::default())
+    }
+
+    /// Collects `iter` after checking in linear time that it is sorted and deduplicated.
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Result<Self, FromSortedError> {
+        Self::from_sorted_vec(Vec::from_iter(iter))
+    }
+
+    /// Wraps `vec` without checking it.
+    ///
+    /// # Safety
+    ///
+    /// `vec` must be sorted and deduplicated by the comparator.
+    /// Every method of the set relies on this, and may otherwise return wrong results or panic.
+    pub unsafe fn from_sorted_unchecked(vec: Vec<T>) -> Self {
+        Self(vec, C::default())
+    }
+}
+
+/// Lookups with [`Comparable`] queries, which follow the natural order of the elements, so these
+/// are only available on sets in that order. With another comparator, use `get` and the other
+/// lookups, which search with the comparator:
+///
+/// ```compile_fail
+/// use binary_tree_collections::compare::{Natural, Reverse};
+/// use binary_tree_collections::set::BinarySetBy;
+///
+/// let set = BinarySetBy::from_iter_with_comparator([1, 2], Reverse(Natural));
+/// set.range_by(2..=1);
+/// ```
+impl<T, S: SortedStorage> BinarySetBy<T, Natural, S> {
+    /// Like `get`, but accepts any query that is [`Comparable`] with the elements.
+    /// The query must agree with the order of the set.
+    pub fn get_by<Q: ?Sized + Comparable<T>>(&self, key: &Q) -> Option<&T> {
+        let index = search::find(&self.0, key, |q, x| q.compare(x)).ok()?;
+        self.0.get(index)
+    }
+
+    /// Like [`BinarySetBy::range_slice`], but accepts any bounds that are [`Comparable`] with the elements
+    /// and with each other.
+    pub fn range_by<Q, R>(&self, range: R) -> &[T]
+    where
+        Q: ?Sized + Comparable<T> + Comparable<Q>,
+        R: RangeBounds<Q>,
+    {
+        search::check_bounds(&range, Comparable::<Q>::compare);
+        &self.0[search::range(&self.0, &range, |q, x| q.compare(x))]
+    }
+
+    pub fn remove_by<Q: ?Sized + Comparable<T>>(&mut self, key: &Q) -> Option<T> {
+        let index = search::find(&self.0, key, |q, x| q.compare(x)).ok()?;
+        Some(self.0.remove(index))
+    }
+}
+
+impl<T, C, S: SortedStorage> BinarySetBy<T, C, S> {
+    /// The position of `key` if it is present, or else where it would be inserted.
+    pub fn index_of<Q: ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        C: Compare<Q, T>,
+    {
+        self.find(key)
+    }
+
+    /// The number of elements less than `key`.
+    pub fn rank<Q: ?Sized>(&self, key: &Q) -> usize
+    where
+        C: Compare<Q, T>,
+    {
+        self.find(key).unwrap_or_else(|index| index)
+    }
+
+    /// The element at position `index` in order.
+    pub fn get_index(&self, index: usize) -> Option<&T> {
+        self.0.get(index)
+    }
+
+    /// The element at position `index` in order.
+    /// It is a logic error to change it in a way that moves it relative to the other elements.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.0.get_mut(index)
+    }
+
+    /// Removes the element at position `index`, shifting the ones after it down.
+    pub fn remove_index(&mut self, index: usize) -> Option<T> {
+        (index < self.0.len()).then(|| self.0.remove(index))
+    }
+
+    /// Keeps only the elements for which `keep` returns true.
+    pub fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        self.0.retain(keep)
+    }
+
+    /// The greatest element less than or equal to `key`.
+    pub fn floor<Q: ?Sized>(&self, key: &Q) -> Option<&T>
+    where
+        C: Compare<Q, T>,
+    {
+        let index = search::upper_bound(&self.0, key, |q, x| self.1.compare(q, x));
+        self.0.get(index.checked_sub(1)?)
+    }
+
+    /// The least element greater than or equal to `key`.
+    pub fn ceiling<Q: ?Sized>(&self, key: &Q) -> Option<&T>
+    where
+        C: Compare<Q, T>,
+    {
+        let index = search::lower_bound(&self.0, key, |q, x| self.1.compare(q, x));
+        self.0.get(index)
+    }
+
+    /// The greatest element strictly less than `key`.
+    pub fn lower<Q: ?Sized>(&self, key: &Q) -> Option<&T>
+    where
+        C: Compare<Q, T>,
+    {
+        let index = search::lower_bound(&self.0, key, |q, x| self.1.compare(q, x));
+        self.0.get(index.checked_sub(1)?)
+    }
+
+    /// The least element strictly greater than `key`.
+    pub fn higher<Q: ?Sized>(&self, key: &Q) -> Option<&T>
+    where
+        C: Compare<Q, T>,
+    {
+        let index = search::upper_bound(&self.0, key, |q, x| self.1.compare(q, x));
+        self.0.get(index)
+    }
+
+    /// The element nearest to `key` by `distance`, which must grow with distance in the order
+    /// of the set. Ties go to the smaller element.
+    pub fn nearest<Q: ?Sized, D: PartialOrd>(
+        &self,
+        key: &Q,
+        distance: impl Fn(&Q, &T) -> D,
+    ) -> Option<&T>
+    where
+        C: Compare<Q, T>,
+    {
+        let index = search::nearest(&self.0, key, |q, x| self.1.compare(q, x), distance)?;
+        self.0.get(index)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.0.first()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.0.last()
+    }
+
+    /// Removes the smallest element. This shifts every other element down.
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.remove_index(0)
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> BinarySetBy<T, C, S> {
+    /// Visits the elements in `self` or `other`, in ascending order.
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T, C> {
+        Union {
+            a: &self.0[..],
+            b: &other.0[..],
+            compare: &self.1,
+        }
+    }
+
+    /// Visits the elements in both `self` and `other`, in ascending order.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T, C> {
+        Intersection {
+            a: &self.0[..],
+            b: &other.0[..],
+            compare: &self.1,
+        }
+    }
+
+    /// Visits the elements in `self` but not in `other`, in ascending order.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T, C> {
+        Difference {
+            a: &self.0[..],
+            b: &other.0[..],
+            compare: &self.1,
+        }
+    }
+
+    /// Visits the elements in exactly one of `self` and `other`, in ascending order.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T, C> {
+        SymmetricDifference {
+            a: &self.0[..],
+            b: &other.0[..],
+            compare: &self.1,
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    /// Adds the elements of `other` to `self`. Elements already in `self` are kept.
+    pub fn union_with(&mut self, other: Self) {
+        let mut other = other.0.into_iter();
+        self.insert_inline(&mut other, false);
+        let compare = &self.1;
+        if let Some(vec) = self.0.as_vec_mut() {
+            merge::union(vec, Vec::from_iter(other), |a, b| compare.compare(a, b));
+        }
+    }
+
+    /// Removes the elements of `self` that are not in `other`.
+    pub fn intersect_with(&mut self, other: &Self) {
+        let compare = &self.1;
+        merge::intersection(&mut self.0, &other.0, |a, b| compare.compare(a, b))
+    }
+
+    /// Removes the elements of `self` that are in `other`.
+    pub fn difference_with(&mut self, other: &Self) {
+        let compare = &self.1;
+        merge::difference(&mut self.0, &other.0, |a, b| compare.compare(a, b))
+    }
+
+    /// Keeps the elements in exactly one of `self` and `other`.
+    pub fn symmetric_difference_with(&mut self, mut other: Self) {
+        let compare = &self.1;
+        merge::remove_shared(&mut self.0, &mut other.0, |a, b| compare.compare(a, b));
+        self.union_with(other);
+    }
+
+    pub fn into_union(mut self, other: Self) -> Self {
+        self.union_with(other);
+        self
+    }
+
+    pub fn into_intersection(mut self, other: &Self) -> Self {
+        self.intersect_with(other);
+        self
+    }
+
+    pub fn into_difference(mut self, other: &Self) -> Self {
+        self.difference_with(other);
+        self
+    }
+
+    pub fn into_symmetric_difference(mut self, other: Self) -> Self {
+        self.symmetric_difference_with(other);
+        self
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone, S: SortedStorage> BitOr<&BinarySetBy<T, C, S>>
+    for &BinarySetBy<T, C, S>
+{
+    type Output = BinarySetBy<T, C, S>;
+
+    fn bitor(self, rhs: &BinarySetBy<T, C, S>) -> Self::Output {
+        BinarySetBy(self.union(rhs).cloned().collect(), self.1.clone())
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone, S: SortedStorage> BitAnd<&BinarySetBy<T, C, S>>
+    for &BinarySetBy<T, C, S>
+{
+    type Output = BinarySetBy<T, C, S>;
+
+    fn bitand(self, rhs: &BinarySetBy<T, C, S>) -> Self::Output {
+        BinarySetBy(self.intersection(rhs).cloned().collect(), self.1.clone())
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone, S: SortedStorage> Sub<&BinarySetBy<T, C, S>>
+    for &BinarySetBy<T, C, S>
+{
+    type Output = BinarySetBy<T, C, S>;
+
+    fn sub(self, rhs: &BinarySetBy<T, C, S>) -> Self::Output {
+        BinarySetBy(self.difference(rhs).cloned().collect(), self.1.clone())
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone, S: SortedStorage> BitXor<&BinarySetBy<T, C, S>>
+    for &BinarySetBy<T, C, S>
+{
+    type Output = BinarySetBy<T, C, S>;
+
+    fn bitxor(self, rhs: &BinarySetBy<T, C, S>) -> Self::Output {
+        BinarySetBy(
+            self.symmetric_difference(rhs).cloned().collect(),
+            self.1.clone(),
+        )
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> BitOrAssign for BinarySetBy<T, C, S> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.union_with(rhs)
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone, S: SortedStorage> BitOrAssign<&BinarySetBy<T, C, S>>
+    for BinarySetBy<T, C, S>
+{
+    fn bitor_assign(&mut self, rhs: &Self) {
+        // only clone the elements that are missing
+        let missing = BinarySetBy(rhs.difference(self).cloned().collect(), self.1.clone());
+        self.union_with(missing)
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> BitAndAssign for BinarySetBy<T, C, S> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.intersect_with(&rhs)
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> BitAndAssign<&BinarySetBy<T, C, S>>
+    for BinarySetBy<T, C, S>
+{
+    fn bitand_assign(&mut self, rhs: &Self) {
+        self.intersect_with(rhs)
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> SubAssign for BinarySetBy<T, C, S> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.difference_with(&rhs)
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> SubAssign<&BinarySetBy<T, C, S>> for BinarySetBy<T, C, S> {
+    fn sub_assign(&mut self, rhs: &Self) {
+        self.difference_with(rhs)
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> BitXorAssign for BinarySetBy<T, C, S> {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.symmetric_difference_with(rhs)
+    }
+}
+
+impl<T: Clone, C: Compare<T> + Clone, S: SortedStorage> BitXorAssign<&BinarySetBy<T, C, S>>
+    for BinarySetBy<T, C, S>
+{
+    fn bitxor_assign(&mut self, rhs: &Self) {
+        let rhs = Self(rhs.0.iter().cloned().collect(), rhs.1.clone());
+        self.symmetric_difference_with(rhs)
+    }
+}
+
+impl<T, C: Compare<T>, S: SortedStorage> Extend<T> for BinarySetBy<T, C, S> {
+    /// Picks an [`ExtendStrategy`] from the size of the batch and where it lands in the set.
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        self.0.extend(iter);
-        self.0.sort();
-        self.0.dedup();
+        self.extend_with_strategy(iter, ExtendStrategy::Auto);
     }
 }
 
-impl<T> From<BinarySet<T>> for Vec<T> {
-    fn from(val: BinarySet<T>) -> Self {
-        val.0
+impl<T: PartialEq, C, S: SortedStorage> PartialEq for BinarySetBy<T, C, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0[..] == other.0[..]
     }
 }
 
-impl<T> Clear for BinarySet<T> {
+impl<T: Eq, C, S: SortedStorage> Eq for BinarySetBy<T, C, S> {}
+
+impl<T: PartialOrd, C, S: SortedStorage> PartialOrd for BinarySetBy<T, C, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0[..].partial_cmp(&other.0[..])
+    }
+}
+
+impl<T: Ord, C, S: SortedStorage> Ord for BinarySetBy<T, C, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0[..].cmp(&other.0[..])
+    }
+}
+
+impl<T, C, S: SortedStorage> From<BinarySetBy<T, C, S>> for Vec<T> {
+    fn from(val: BinarySetBy<T, C, S>) -> Self {
+        val.0.into()
+    }
+}
+
+impl<T, C, S: SortedStorage> Clear for BinarySetBy<T, C, S> {
     fn clear(&mut self) {
-        self.0.clear()
+        self.0.truncate(0)
     }
 }
 
-impl<T> AsRef<Vec<T>> for BinarySet<T> {
+impl<T, C> AsRef<Vec<T>> for BinarySetBy<T, C> {
     fn as_ref(&self) -> &Vec<T> {
         &self.0
     }
 }
 
+impl<T, C, S: SortedStorage> IntoIterator for BinarySetBy<T, C, S> {
+    type Item = T;
+    type IntoIter = <S::Buffer<T> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T, C, S: SortedStorage> IntoIterator for &'a BinarySetBy<T, C, S> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
 impl<T> BinarySet<T> {}
 
-impl<T: Ord + PartialOrd + Eq + PartialEq> FromIterator<T> for BinarySet<T> {
+impl<T, C: Compare<T> + Default, S: SortedStorage> FromIterator<T> for BinarySetBy<T, C, S> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut vec: Vec<T> = Vec::from_iter(iter);
-        vec.sort();
-        vec.dedup();
-
-        Self(vec)
+        Self::collect_with_comparator(iter, C::default())
     }
 }
 
-impl<T> Reserve for BinarySet<T> {
+impl<T, C, S: SortedStorage> Reserve for BinarySetBy<T, C, S> {
     fn reserve(&mut self, additional: usize) {
         self.0.reserve(additional)
     }
 }
 
-impl<T> WithCapacity for BinarySet<T> {
+impl<T, C: Default, S: SortedStorage> WithCapacity for BinarySetBy<T, C, S> {
     fn with_capacity(capacity: usize) -> Self {
-        Self(Vec::with_capacity(capacity))
+        Self(S::Buffer::with_capacity(capacity), C::default())
     }
 }
 
-impl<T> Capacity for BinarySet<T> {
+impl<T, C, S: SortedStorage> Capacity for BinarySetBy<T, C, S> {
     fn capacity(&self) -> usize {
         self.0.capacity()
     }
 }
 
-impl<T> Iter for BinarySet<T> {
+impl<T, C, S: SortedStorage> Iter for BinarySetBy<T, C, S> {
     type Iter<'a> = core::slice::Iter<'a, T>
 	    where
 		    Self: 'a;
@@ -142,11 +834,11 @@ impl<T> Iter for BinarySet<T> {
     }
 }
 
-impl<T: Ord + PartialOrd + Eq + PartialEq> Insert for BinarySet<T> {
+impl<T, C: Compare<T>, S: SortedStorage> Insert for BinarySetBy<T, C, S> {
     type Output = bool;
 
     fn insert(&mut self, element: Self::Item) -> Self::Output {
-        match self.0.binary_search(&element) {
+        match self.find(&element) {
             Ok(_) => false,
             Err(index) => {
                 self.0.insert(index, element);
@@ -156,29 +848,23 @@ impl<T: Ord + PartialOrd + Eq + PartialEq> Insert for BinarySet<T> {
     }
 }
 
-impl<'a, T: Ord + PartialOrd + Eq + PartialEq> Remove<&'a T> for BinarySet<T> {
-    fn remove(&mut self, key: &'a T) -> Option<Self::Item> {
-        match self.0.binary_search(key) {
+impl<'a, Q: ?Sized, T, C: Compare<Q, T>, S: SortedStorage> Remove<&'a Q> for BinarySetBy<T, C, S> {
+    fn remove(&mut self, key: &'a Q) -> Option<Self::Item> {
+        match self.find(key) {
             Ok(index) => Some(self.0.remove(index)),
             Err(_) => None,
         }
     }
 }
 
-impl<T: Ord + PartialOrd + Eq + PartialEq> Get<T> for BinarySet<T> {
-    fn get(&self, key: T) -> Option<Self::ItemRef<'_>> {
-        self.get(&key)
-    }
-}
-
-impl<'a, T: Ord + PartialOrd + Eq + PartialEq> Get<&'a T> for BinarySet<T> {
-    fn get(&self, key: &'a T) -> Option<Self::ItemRef<'_>> {
-        let index = self.0.binary_search(key).ok()?;
+impl<'a, Q: ?Sized, T, C: Compare<Q, T>, S: SortedStorage> Get<&'a Q> for BinarySetBy<T, C, S> {
+    fn get(&self, key: &'a Q) -> Option<Self::ItemRef<'_>> {
+        let index = self.find(key).ok()?;
         self.0.get(index)
     }
 }
 
-impl<T> CollectionRef for BinarySet<T> {
+impl<T, C, S: SortedStorage> CollectionRef for BinarySetBy<T, C, S> {
     type ItemRef<'a>= &'a T
 	    where
 		    Self: 'a ;
@@ -186,10 +872,14 @@ impl<T> CollectionRef for BinarySet<T> {
     covariant_item_ref!();
 }
 
-impl<T> Collection for BinarySet<T> {
+impl<T, C, S: SortedStorage> SimpleCollectionRef for BinarySetBy<T, C, S> {
+    simple_collection_ref!();
+}
+
+impl<T, C, S: SortedStorage> Collection for BinarySetBy<T, C, S> {
     type Item = T;
 }
-impl<T> Len for BinarySet<T> {
+impl<T, C, S: SortedStorage> Len for BinarySetBy<T, C, S> {
     fn len(&self) -> usize {
         self.0.len()
     }
@@ -209,8 +899,8 @@ pub mod tests {
     pub fn get() {
         let set = BinarySet::from_iter([1, 2, 3]);
 
-        assert_eq!(set.get(2), Some(&2));
-        assert_eq!(set.get(4), None);
+        assert_eq!(set.get(&2), Some(&2));
+        assert_eq!(set.get(&4), None);
     }
 
     #[test]
@@ -233,6 +923,21 @@ pub mod tests {
         assert_eq!(vec, vec![1, 2, 3])
     }
 
+    #[test]
+    pub fn into_iter() {
+        let set = BinarySet::from_iter([3, 1, 2]);
+        let mut vec = vec![];
+        for x in &set {
+            vec.push(*x);
+        }
+        assert_eq!(vec, vec![1, 2, 3]);
+
+        let mut iter = set.into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![1, 2]);
+    }
+
     #[test]
     pub fn extend_prepend() {
         let mut set = BinarySet::from_iter([4, 5, 6]);
@@ -307,4 +1012,541 @@ pub mod tests {
 
         assert_eq!(into_vec, vec![])
     }
+
+    #[test]
+    pub fn union() {
+        let a = BinarySet::from_iter([1, 3, 5, 7]);
+        let b = BinarySet::from_iter([2, 3, 4, 8]);
+        let vec: Vec<i32> = a.union(&b).cloned().collect();
+        assert_eq!(vec, vec![1, 2, 3, 4, 5, 7, 8]);
+
+        assert_eq!(a.into_union(b).as_ref(), &vec)
+    }
+
+    #[test]
+    pub fn intersection() {
+        let a = BinarySet::from_iter([1, 3, 5, 7]);
+        let b = BinarySet::from_iter([2, 3, 4, 7, 8]);
+        let vec: Vec<i32> = a.intersection(&b).cloned().collect();
+        assert_eq!(vec, vec![3, 7]);
+
+        assert_eq!(a.into_intersection(&b).as_ref(), &vec)
+    }
+
+    #[test]
+    pub fn difference() {
+        let a = BinarySet::from_iter([1, 3, 5, 7]);
+        let b = BinarySet::from_iter([2, 3, 4, 7, 8]);
+        let vec: Vec<i32> = a.difference(&b).cloned().collect();
+        assert_eq!(vec, vec![1, 5]);
+
+        assert_eq!(a.into_difference(&b).as_ref(), &vec)
+    }
+
+    #[test]
+    pub fn symmetric_difference() {
+        let a = BinarySet::from_iter([1, 3, 5, 7]);
+        let b = BinarySet::from_iter([2, 3, 4, 7, 8]);
+        let vec: Vec<i32> = a.symmetric_difference(&b).cloned().collect();
+        assert_eq!(vec, vec![1, 2, 4, 5, 8]);
+
+        assert_eq!(a.into_symmetric_difference(b).as_ref(), &vec)
+    }
+
+    #[test]
+    pub fn subset_superset_disjoint() {
+        let a = BinarySet::from_iter([1, 2, 3]);
+        let b = BinarySet::from_iter([0, 1, 2, 3, 4]);
+        let c = BinarySet::from_iter([4, 5]);
+
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(b.is_superset(&a));
+        assert!(a.is_disjoint(&c));
+        assert!(!b.is_disjoint(&c));
+    }
+
+    #[test]
+    pub fn set_operations_match_btree_set() {
+        use std::collections::BTreeSet;
+
+        // covers both the galloping and the linear paths
+        for (a_len, b_len) in [(0, 5), (5, 0), (50, 40), (500, 7), (7, 500), (1, 1000)] {
+            let a: Vec<u32> = (0..a_len).map(|x| (x * 7919) % 1013).collect();
+            let b: Vec<u32> = (0..b_len).map(|x| (x * 104729) % 1013).collect();
+            let (set_a, set_b) = (
+                BinarySet::from_iter(a.clone()),
+                BinarySet::from_iter(b.clone()),
+            );
+            let (tree_a, tree_b) = (BTreeSet::from_iter(a), BTreeSet::from_iter(b));
+
+            let expected: Vec<u32> = tree_a.union(&tree_b).cloned().collect();
+            assert_eq!(set_a.clone().into_union(set_b.clone()).as_ref(), &expected);
+
+            let expected: Vec<u32> = tree_a.intersection(&tree_b).cloned().collect();
+            assert_eq!(set_a.clone().into_intersection(&set_b).as_ref(), &expected);
+
+            let expected: Vec<u32> = tree_a.difference(&tree_b).cloned().collect();
+            assert_eq!(set_a.clone().into_difference(&set_b).as_ref(), &expected);
+
+            let expected: Vec<u32> = tree_a.symmetric_difference(&tree_b).cloned().collect();
+            assert_eq!(
+                set_a
+                    .clone()
+                    .into_symmetric_difference(set_b.clone())
+                    .as_ref(),
+                &expected
+            );
+
+            assert_eq!(set_a.is_subset(&set_b), tree_a.is_subset(&tree_b));
+            assert_eq!(set_a.is_disjoint(&set_b), tree_a.is_disjoint(&tree_b));
+        }
+    }
+
+    #[test]
+    pub fn union_with_keeps_existing_elements() {
+        #[derive(Debug, Clone)]
+        struct Tagged(u8, &'static str);
+        impl PartialEq for Tagged {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Tagged {}
+        impl PartialOrd for Tagged {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Tagged {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let mut set = BinarySet::from_iter([Tagged(1, "old"), Tagged(3, "old")]);
+        set.union_with(BinarySet::from_iter([Tagged(2, "new"), Tagged(3, "new")]));
+
+        let tags: Vec<_> = set.iter().map(|x| (x.0, x.1)).collect();
+        assert_eq!(tags, vec![(1, "old"), (2, "new"), (3, "old")]);
+    }
+
+    #[test]
+    pub fn union_with_panicking_ord_stays_sorted() {
+        use std::cell::Cell;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::rc::Rc;
+
+        #[derive(Debug)]
+        struct Bomb(u32, Rc<Cell<usize>>);
+        impl PartialEq for Bomb {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Bomb {}
+        impl PartialOrd for Bomb {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Bomb {
+            fn cmp(&self, other: &Self) -> Ordering {
+                let fuse = self.1.get();
+                if fuse == 0 {
+                    panic!("boom");
+                }
+                self.1.set(fuse - 1);
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let fuse = Rc::new(Cell::new(usize::MAX));
+        let mut set = BinarySet::from_iter((0..20).map(|x| Bomb(x * 2, fuse.clone())));
+        let other = BinarySet::from_iter((0..20).map(|x| Bomb(x * 2 + 1, fuse.clone())));
+
+        fuse.set(15);
+        let result = catch_unwind(AssertUnwindSafe(|| set.union_with(other)));
+        assert!(result.is_err());
+        fuse.set(usize::MAX);
+
+        let values: Vec<u32> = set.iter().map(|x| x.0).collect();
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        assert!((0..20).all(|x| values.binary_search(&(x * 2)).is_ok()));
+        // every element is either in the set or dropped exactly once
+        assert_eq!(Rc::strong_count(&fuse), values.len() + 1);
+        drop(set);
+
+        // panicking while sorting, deduplicating and merging the batch
+        for compares in [5, 60, 90, 120, 170] {
+            let mut set = BinarySet::from_iter((0..20).map(|x| Bomb(x * 2, fuse.clone())));
+            let batch = (10..50).rev().chain(10..15).map(|x| Bomb(x, fuse.clone()));
+            fuse.set(compares);
+            let result = catch_unwind(AssertUnwindSafe(|| set.extend_with_merge(batch)));
+            assert!(result.is_err(), "{compares}");
+            fuse.set(usize::MAX);
+
+            let values: Vec<u32> = set.iter().map(|x| x.0).collect();
+            assert!(values.windows(2).all(|w| w[0] < w[1]));
+            assert!((0..20).all(|x| values.binary_search(&(x * 2)).is_ok()));
+            assert_eq!(Rc::strong_count(&fuse), values.len() + 1);
+        }
+    }
+
+    #[test]
+    pub fn operators() {
+        let a = BinarySet::from_iter([1, 2, 3]);
+        let b = BinarySet::from_iter([2, 3, 4]);
+
+        assert_eq!((&a | &b).as_ref(), &vec![1, 2, 3, 4]);
+        assert_eq!((&a & &b).as_ref(), &vec![2, 3]);
+        assert_eq!((&a - &b).as_ref(), &vec![1]);
+        assert_eq!((&a ^ &b).as_ref(), &vec![1, 4]);
+    }
+
+    #[test]
+    pub fn assign_operators() {
+        let a = BinarySet::from_iter([1, 2, 3]);
+        let b = BinarySet::from_iter([2, 3, 4]);
+
+        let mut set = a.clone();
+        set |= &b;
+        assert_eq!(set.as_ref(), &vec![1, 2, 3, 4]);
+
+        let mut set = a.clone();
+        set &= &b;
+        assert_eq!(set.as_ref(), &vec![2, 3]);
+
+        let mut set = a.clone();
+        set -= &b;
+        assert_eq!(set.as_ref(), &vec![1]);
+
+        let mut set = a.clone();
+        set ^= &b;
+        assert_eq!(set.as_ref(), &vec![1, 4]);
+
+        let mut set = a;
+        set |= b.clone();
+        set -= BinarySet::from_iter([1]);
+        set ^= BinarySet::from_iter([4, 5]);
+        set &= b;
+        assert_eq!(set.as_ref(), &vec![2, 3]);
+    }
+
+    #[test]
+    pub fn range() {
+        use std::ops::Bound;
+
+        let set = BinarySet::from_iter([1, 2, 3, 4]);
+        assert_eq!(set.range_slice(2..=3), &[2, 3]);
+        assert_eq!(set.range_slice(2..4), &[2, 3]);
+        assert_eq!(
+            set.range_slice((Bound::Excluded(1), Bound::Included(3))),
+            &[2, 3]
+        );
+        assert_eq!(set.range_slice(2..5), &[2, 3, 4]);
+        assert_eq!(set.range_slice(..3), &[1, 2]);
+        assert_eq!(set.range_slice(-1..3), &[1, 2]);
+        assert_eq!(set.range_slice(5..), &[] as &[i32]);
+
+        let mut range = set.range(1..4);
+        assert_eq!(range.len(), 3);
+        assert_eq!(range.next_back(), Some(&3));
+        assert_eq!(range.next(), Some(&1));
+        assert_eq!(range.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    pub fn range_backwards() {
+        use std::ops::Bound;
+
+        let set = BinarySet::from_iter([1, 2, 3, 4]);
+        set.range_slice((Bound::Included(3), Bound::Excluded(2)));
+    }
+
+    #[test]
+    pub fn borrowed_lookups() {
+        use std::ops::Bound;
+
+        let mut set = BinarySet::from_iter([vec![1u8, 2], vec![3], vec![4, 5]]);
+
+        assert_eq!(set.get([3u8].as_slice()), Some(&vec![3]));
+        let range = (Bound::Included([2u8].as_slice()), Bound::Unbounded);
+        assert_eq!(set.range_slice::<[u8], _>(range).len(), 2);
+        assert_eq!(set.remove([1u8, 2].as_slice()), Some(vec![1, 2]));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    pub fn comparable_lookups() {
+        use crate::comparable::Equivalent;
+
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Prefix(u8);
+
+        impl Equivalent<(u8, u8)> for Prefix {
+            fn equivalent(&self, key: &(u8, u8)) -> bool {
+                self.0 == key.0
+            }
+        }
+
+        impl Comparable<(u8, u8)> for Prefix {
+            fn compare(&self, key: &(u8, u8)) -> Ordering {
+                self.0.cmp(&key.0)
+            }
+        }
+
+        let mut set = BinarySet::from_iter([(1, 1), (2, 1), (2, 2), (3, 1)]);
+
+        assert_eq!(set.range_by(Prefix(2)..=Prefix(2)), &[(2, 1), (2, 2)]);
+        assert_eq!(set.get_by(&Prefix(3)), Some(&(3, 1)));
+        assert_eq!(set.remove_by(&Prefix(1)), Some((1, 1)));
+        assert_eq!(set.get_by(&Prefix(1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start and end are equal and excluded")]
+    pub fn range_by_equal_excluded_bounds() {
+        use std::ops::Bound;
+
+        let set = BinarySet::from_iter([1, 2, 3]);
+        set.range_by((Bound::Excluded(2), Bound::Excluded(2)));
+    }
+
+    #[test]
+    pub fn extend_with_rotates_interior() {
+        let mut set = BinarySet::from_iter([1, 2, 3, 10]);
+        set.extend_with_rotates([5, 11, 2]);
+        assert_eq!(set.as_ref(), &vec![1, 2, 3, 5, 10, 11]);
+    }
+
+    #[test]
+    pub fn extend_strategies_agree() {
+        let batches: [&[u32]; 5] = [
+            &[7],
+            &[2, 2, 4],
+            &[0, 1, 1, 2],
+            &[9, 3, 5, 8, 1, 6],
+            &[20, 15, 11],
+        ];
+        for batch in batches {
+            let expected = BinarySet::from_iter([1, 3, 5, 7, 9].iter().chain(batch).cloned());
+            for strategy in [
+                ExtendStrategy::Auto,
+                ExtendStrategy::Sort,
+                ExtendStrategy::Inserts,
+                ExtendStrategy::Rotates,
+                ExtendStrategy::Merge,
+            ] {
+                let mut set = BinarySet::from_iter([1, 3, 5, 7, 9]);
+                set.extend_with_strategy(batch.iter().cloned(), strategy);
+                assert_eq!(set, expected, "{strategy:?} with {batch:?}");
+            }
+        }
+    }
+
+    #[test]
+    pub fn extend_chooses_strategy() {
+        let choose = |set: &BinarySet<u32>, batch: &[u32]| {
+            let mut set = set.clone();
+            let start = set.0.len();
+            set.0.extend_from_slice(batch);
+            set.choose_strategy(start)
+        };
+        let set = BinarySet::from_iter(10..50);
+        assert_eq!(choose(&set, &[3, 1]), ExtendStrategy::Inserts);
+
+        let set = BinarySet::from_iter(10..20);
+        let batches: [(&[u32], _); 5] = [
+            (&[3, 1], ExtendStrategy::Rotates),
+            (&[5, 1, 4, 3, 2], ExtendStrategy::Rotates),
+            (&[25, 21, 24, 23, 22], ExtendStrategy::Merge),
+            (&[5, 11, 24, 13, 22], ExtendStrategy::Merge),
+            (&[10, 5, 4, 3, 2], ExtendStrategy::Merge),
+        ];
+        for (batch, strategy) in batches {
+            assert_eq!(choose(&set, batch), strategy, "{batch:?}");
+        }
+
+        let set = BinarySet::default();
+        assert_eq!(choose(&set, &[2, 1]), ExtendStrategy::Sort);
+    }
+
+    #[test]
+    pub fn positional() {
+        let mut set = BinarySet::from_iter([10, 20, 30, 40]);
+        assert_eq!(set.index_of(&30), Ok(2));
+        assert_eq!(set.index_of(&25), Err(2));
+        assert_eq!(set.rank(&30), 2);
+        assert_eq!(set.rank(&45), 4);
+        assert_eq!(set.get_index(1), Some(&20));
+        assert_eq!(set.get_index(4), None);
+
+        *set.get_index_mut(1).unwrap() = 21;
+        assert_eq!(set.remove_index(1), Some(21));
+        assert_eq!(set.remove_index(3), None);
+        assert_eq!((set.first(), set.last()), (Some(&10), Some(&40)));
+        assert_eq!(set.pop_first(), Some(10));
+        assert_eq!(set.pop_last(), Some(40));
+        assert_eq!(set.pop_last(), Some(30));
+        assert_eq!(set.pop_first(), None);
+    }
+
+    #[test]
+    pub fn neighbours() {
+        let set = BinarySet::from_iter([10, 20, 30]);
+        for (key, floor, ceiling, lower, higher) in [
+            (5, None, Some(&10), None, Some(&10)),
+            (10, Some(&10), Some(&10), None, Some(&20)),
+            (15, Some(&10), Some(&20), Some(&10), Some(&20)),
+            (30, Some(&30), Some(&30), Some(&20), None),
+            (35, Some(&30), None, Some(&30), None),
+        ] {
+            assert_eq!(set.floor(&key), floor, "floor of {key}");
+            assert_eq!(set.ceiling(&key), ceiling, "ceiling of {key}");
+            assert_eq!(set.lower(&key), lower, "lower of {key}");
+            assert_eq!(set.higher(&key), higher, "higher of {key}");
+        }
+
+        let distance = |a: &i32, b: &i32| a.abs_diff(*b);
+        assert_eq!(set.nearest(&14, distance), Some(&10));
+        assert_eq!(set.nearest(&15, distance), Some(&10));
+        assert_eq!(set.nearest(&16, distance), Some(&20));
+        assert_eq!(set.nearest(&99, distance), Some(&30));
+        assert_eq!(BinarySet::default().nearest(&1, distance), None);
+    }
+
+    #[test]
+    pub fn bulk_mutation() {
+        let mut set = BinarySet::from_iter(0..10);
+        set.retain(|x| x % 3 != 0);
+        assert_eq!(set.as_ref(), &vec![1, 2, 4, 5, 7, 8]);
+
+        assert_eq!(set.drain_range(2..=5).collect::<Vec<_>>(), vec![2, 4, 5]);
+        assert_eq!(set.extract_if(|x| x % 2 == 0).collect::<Vec<_>>(), vec![8]);
+        assert_eq!(set.as_ref(), &vec![1, 7]);
+
+        let mut high = set.split_off(&5);
+        assert_eq!((set.as_ref(), high.as_ref()), (&vec![1], &vec![7]));
+
+        high.extend([3, 9]);
+        set.append(&mut high);
+        assert_eq!(set.as_ref(), &vec![1, 3, 7, 9]);
+        assert!(high.is_empty());
+        assert_eq!(set.drain().count(), 4);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    pub fn append_prefers_other() {
+        use crate::compare::CaseInsensitive;
+
+        let mut set = BinarySetBy::from_iter_with_comparator(["a", "B", "c"], CaseInsensitive);
+        let mut other = BinarySetBy::from_iter_with_comparator(["b", "D"], CaseInsensitive);
+        set.append(&mut other);
+        assert_eq!(set.as_ref(), &vec!["a", "b", "c", "D"]);
+    }
+
+    #[test]
+    pub fn small_set_stays_inline() {
+        let mut set: SmallBinarySet<u32, 8> = [5, 3, 1, 3, 7].into_iter().collect();
+        assert_eq!(&set.buffer()[..], &[1, 3, 5, 7]);
+        set.extend([0, 2, 4]);
+        assert!(set.insert(6));
+        assert!(!set.buffer().spilled());
+        assert_eq!(set.remove(&0), Some(0));
+        assert_eq!(set.range(2..5).copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert!(set.is_subset(&(0..8).collect()));
+
+        let mut high = set.split_off(&4);
+        set.append(&mut high);
+        set.extend(8..12);
+        assert!(set.buffer().spilled());
+        assert_eq!(set.into_storage::<VecStore>(), BinarySet::from_iter(1..12));
+    }
+
+    #[test]
+    pub fn from_sorted() {
+        use crate::compare::Reverse;
+        use crate::error::FromSortedError;
+
+        let set = BinarySet::from_sorted_vec(vec![1, 2, 4]).unwrap();
+        assert_eq!(set.as_ref(), &vec![1, 2, 4]);
+        assert_eq!(
+            BinarySet::from_sorted_iter([1, 3, 2]),
+            Err(FromSortedError::Unsorted { index: 2 })
+        );
+        assert_eq!(
+            BinarySet::from_sorted_vec(vec![1, 1]),
+            Err(FromSortedError::Duplicate { index: 1 })
+        );
+        assert!(BinarySet::<u8>::from_sorted_iter([]).unwrap().is_empty());
+
+        let set = BinarySetBy::from_sorted_vec_with_comparator(vec![3, 2, 1], Reverse(Natural));
+        assert_eq!(set.unwrap().as_ref(), &vec![3, 2, 1]);
+
+        // SAFETY: the vector is sorted and has no duplicates.
+        let set: BinarySet<_> = unsafe { BinarySet::from_sorted_unchecked(vec!["a", "b"]) };
+        assert!(set.contains("b"));
+    }
+
+    #[test]
+    pub fn reverse_comparator_lookups() {
+        use crate::compare::Reverse;
+        use std::ops::Bound;
+
+        let set = BinarySetBy::from_iter_with_comparator([1, 2, 3, 4], Reverse(Natural));
+        assert_eq!(set.get(&4), Some(&4));
+        let range = (Bound::Included(4), Bound::Included(2));
+        assert_eq!(set.range_slice(range), &[4, 3, 2]);
+        assert_eq!(set.range(3..).collect::<Vec<_>>(), vec![&3, &2, &1]);
+    }
+
+    #[test]
+    pub fn extend_with_merge_keeps_first_duplicate() {
+        use crate::compare::CaseInsensitive;
+
+        let mut set = BinarySetBy::from_iter_with_comparator(["b", "D", "f"], CaseInsensitive);
+        set.extend_with_merge(["d", "A", "a", "E", "F", "e", "g"]);
+        assert_eq!(set.as_ref(), &vec!["A", "b", "D", "E", "f", "g"]);
+    }
+
+    #[test]
+    pub fn case_insensitive() {
+        use crate::compare::CaseInsensitive;
+        use std::ops::Bound;
+
+        let mut set = BinarySetBy::from_iter_with_comparator(["b", "A", "a", "C"], CaseInsensitive);
+        assert_eq!(set.as_ref(), &vec!["A", "b", "C"]);
+
+        assert!(!set.insert("B"));
+        set.extend(["d", "D"]);
+        assert_eq!(set.as_ref(), &vec!["A", "b", "C", "d"]);
+        assert_eq!(set.get("c"), Some(&"C"));
+        assert_eq!(
+            set.range::<str, _>((Bound::Included("B"), Bound::Included("c")))
+                .collect::<Vec<_>>(),
+            vec![&"b", &"C"]
+        );
+    }
+
+    #[test]
+    pub fn total_order() {
+        use crate::compare::TotalOrder;
+
+        let mut set =
+            BinarySetBy::from_iter_with_comparator([2.0, f64::NAN, -0.0, 0.0], TotalOrder);
+        set.insert(1.5);
+        let values = set.as_ref();
+        assert_eq!(values.len(), 5);
+        assert_eq!(&values[1..4], &[0.0, 1.5, 2.0]);
+        assert!(values[0] == 0.0 && values[0].is_sign_negative());
+        assert!(values[4].is_nan());
+
+        let mut other = BinarySetBy::with_comparator(TotalOrder);
+        other.insert(2.0);
+        set.intersect_with(&other);
+        assert_eq!(set.as_ref(), &vec![2.0]);
+    }
 }
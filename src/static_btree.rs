@@ -0,0 +1,534 @@
+//! A frozen set stored as an implicit static B-tree (S-tree).
+//!
+//! Keys are packed into nodes of `B` keys, node `k` having children `k * (B + 1) + i + 1`
+//! for `i` in `0..=B`. With the default `B` of 16, a node of 32-bit keys fills one cache line,
+//! and a search touches one line per level. Within a node the keys less than the query are
+//! counted rather than searched for, which integer keys do with SIMD comparisons.
+//!
+//! ```
+//! use binary_tree_collections::set::BinarySet;
+//! use binary_tree_collections::static_btree::StaticBTreeSet;
+//! use cc_traits::Get;
+//!
+//! let set: StaticBTreeSet<u32> = StaticBTreeSet::from(BinarySet::from_iter(0..1000));
+//! assert!(set.contains(&999));
+//! assert!(!set.contains(&1000));
+//! ```
+
+use cc_traits::{
+    covariant_item_ref, simple_collection_ref, Collection, CollectionRef, Get, Len,
+    SimpleCollectionRef,
+};
+use std::iter::FusedIterator;
+#[cfg(target_arch = "x86_64")]
+use std::sync::OnceLock;
+
+use crate::compare::Natural;
+use crate::set::BinarySet;
+
+/// Keys that can be stored in a [`StaticBTreeSet`].
+///
+/// Primitive integers count with SIMD where the target supports it. Other types can use the
+/// default, scalar, implementation with an empty `impl`.
+pub trait NodeKey: Ord + Clone {
+    /// The number of keys in the sorted `node` that are less than `key`.
+    fn count_less(node: &[Self], key: &Self) -> usize {
+        node.iter().filter(|x| *x < key).count()
+    }
+
+    /// The implementation of [`NodeKey::count_less`] that searches use, fetched once per search.
+    /// Keys whose comparisons depend on features detected at runtime pick one here.
+    fn count_less_fn() -> fn(&[Self], &Self) -> usize {
+        Self::count_less
+    }
+}
+
+impl NodeKey for bool {}
+impl NodeKey for char {}
+impl NodeKey for u128 {}
+impl NodeKey for i128 {}
+impl NodeKey for String {}
+impl NodeKey for &str {}
+
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use std::arch::x86_64::*;
+
+    /// Counts the lanes of each 16-byte chunk of `node` that are less than `key`, and the
+    /// remainder with scalar comparisons.
+    ///
+    /// `less_mask` returns the bits of `movemask_epi8` for lanes less than the key, and each
+    /// lane sets `bits_per_lane` of them.
+    #[inline(always)]
+    pub(super) fn count_less<T: Ord + Copy>(
+        node: &[T],
+        key: T,
+        less_mask: impl Fn(__m128i) -> i32,
+        bits_per_lane: u32,
+    ) -> usize {
+        let lanes = 16 / std::mem::size_of::<T>();
+        let chunks = node.chunks_exact(lanes);
+        let remainder = chunks.remainder();
+        let mut count = 0;
+        for chunk in chunks {
+            // SAFETY: the chunk is exactly 16 bytes, and the load is unaligned.
+            let x = unsafe { _mm_loadu_si128(chunk.as_ptr().cast()) };
+            count += less_mask(x).count_ones() / bits_per_lane;
+        }
+        count as usize + remainder.iter().filter(|x| **x < key).count()
+    }
+
+    /// Counts the 64-bit keys of `node` less than `key`, with `flip` xored into both sides of
+    /// each comparison, and `bits` the value of `key` as an `i64`.
+    #[target_feature(enable = "sse4.2")]
+    pub(super) fn count_less_64<T: Ord + Copy>(node: &[T], key: T, bits: i64, flip: i64) -> usize {
+        let flip = _mm_set1_epi64x(flip);
+        let key_lanes = _mm_xor_si128(_mm_set1_epi64x(bits), flip);
+        count_less(
+            node,
+            key,
+            |x| _mm_movemask_epi8(_mm_cmpgt_epi64(key_lanes, _mm_xor_si128(x, flip))),
+            8,
+        )
+    }
+}
+
+/// Implements [`NodeKey`] with SSE2 comparisons. Unsigned keys have their sign bit flipped
+/// so the signed comparisons order them correctly.
+macro_rules! simd_node_key {
+    ($t:ty, $signed:ty, $set1:ident, $cmpgt:ident, $bits:expr, $flip:expr) => {
+        impl NodeKey for $t {
+            fn count_less(node: &[Self], key: &Self) -> usize {
+                #[cfg(target_arch = "x86_64")]
+                // SAFETY: SSE2 is always available on x86_64.
+                unsafe {
+                    use std::arch::x86_64::*;
+                    let flip = $set1($flip as $signed);
+                    let key_lanes = _mm_xor_si128($set1(*key as $signed), flip);
+                    simd::count_less(
+                        node,
+                        *key,
+                        |x| _mm_movemask_epi8($cmpgt(key_lanes, _mm_xor_si128(x, flip))),
+                        $bits,
+                    )
+                }
+                #[cfg(not(target_arch = "x86_64"))]
+                {
+                    node.iter().filter(|x| *x < key).count()
+                }
+            }
+        }
+    };
+}
+
+simd_node_key!(i8, i8, _mm_set1_epi8, _mm_cmpgt_epi8, 1, 0);
+simd_node_key!(u8, i8, _mm_set1_epi8, _mm_cmpgt_epi8, 1, i8::MIN);
+simd_node_key!(i16, i16, _mm_set1_epi16, _mm_cmpgt_epi16, 2, 0);
+simd_node_key!(u16, i16, _mm_set1_epi16, _mm_cmpgt_epi16, 2, i16::MIN);
+simd_node_key!(i32, i32, _mm_set1_epi32, _mm_cmpgt_epi32, 4, 0);
+simd_node_key!(u32, i32, _mm_set1_epi32, _mm_cmpgt_epi32, 4, i32::MIN);
+
+/// 64-bit comparisons need SSE4.2, which is detected once at runtime, with the scalar
+/// comparisons as a fallback.
+macro_rules! simd64_node_key {
+    ($t:ty, $flip:expr) => {
+        impl NodeKey for $t {
+            fn count_less(node: &[Self], key: &Self) -> usize {
+                Self::count_less_fn()(node, key)
+            }
+
+            fn count_less_fn() -> fn(&[Self], &Self) -> usize {
+                #[cfg(target_arch = "x86_64")]
+                {
+                    static COUNT_LESS: OnceLock<fn(&[$t], &$t) -> usize> = OnceLock::new();
+                    *COUNT_LESS.get_or_init(|| {
+                        if std::arch::is_x86_feature_detected!("sse4.2") {
+                            // SAFETY: SSE4.2 is available.
+                            |node, key| unsafe {
+                                simd::count_less_64(node, *key, *key as i64, $flip as i64)
+                            }
+                        } else {
+                            |node, key| node.iter().filter(|x| *x < key).count()
+                        }
+                    })
+                }
+                #[cfg(not(target_arch = "x86_64"))]
+                {
+                    |node, key| node.iter().filter(|x| *x < key).count()
+                }
+            }
+        }
+    };
+}
+
+simd64_node_key!(i64, 0);
+simd64_node_key!(u64, i64::MIN);
+#[cfg(target_pointer_width = "64")]
+simd64_node_key!(isize, 0);
+#[cfg(target_pointer_width = "64")]
+simd64_node_key!(usize, i64::MIN);
+#[cfg(not(target_pointer_width = "64"))]
+impl NodeKey for isize {}
+#[cfg(not(target_pointer_width = "64"))]
+impl NodeKey for usize {}
+
+/// A frozen set stored as an implicit static B-tree with `B` keys per node.
+/// Build it from a [`BinarySet`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StaticBTreeSet<T, const B: usize = 16> {
+    /// Whole nodes, with the slots after the last element in order holding copies of it.
+    data: Vec<T>,
+    len: usize,
+}
+
+const fn child<const B: usize>(k: usize, i: usize) -> usize {
+    k * (B + 1) + i + 1
+}
+
+impl<T, const B: usize> StaticBTreeSet<T, B> {
+    const NONZERO: () = assert!(B > 0, "nodes must hold at least one key");
+
+    fn node_count(&self) -> usize {
+        self.data.len() / B
+    }
+
+    /// Iterates over the elements in order.
+    pub fn iter(&self) -> Iter<'_, T, B> {
+        Iter {
+            data: &self.data,
+            slots: Slots::new(self.node_count(), self.len),
+        }
+    }
+}
+
+impl<T: NodeKey, const B: usize> StaticBTreeSet<T, B> {
+    /// The slot of the first element not less than `key`, if any.
+    fn lower_bound(&self, key: &T) -> Option<usize> {
+        let nodes = self.node_count();
+        let mut result = None;
+        let count_less = T::count_less_fn();
+        let mut k = 0;
+        while k < nodes {
+            let i = count_less(&self.data[k * B..(k + 1) * B], key);
+            if i < B {
+                result = Some(k * B + i);
+            }
+            k = child::<B>(k, i);
+        }
+        result
+    }
+}
+
+impl<T: NodeKey, const B: usize> From<BinarySet<T>> for StaticBTreeSet<T, B> {
+    fn from(set: BinarySet<T>) -> Self {
+        let () = Self::NONZERO;
+        let sorted: Vec<T> = set.into();
+        let len = sorted.len();
+        let nodes = len.div_ceil(B);
+        let mut slots: Vec<Option<T>> = (0..nodes * B).map(|_| None).collect();
+        let last = sorted.last().cloned();
+        for (slot, x) in Slots::<B>::new(nodes, len).zip(sorted) {
+            slots[slot] = Some(x);
+        }
+        let data = slots
+            .into_iter()
+            .map(|x| x.or_else(|| last.clone()).unwrap())
+            .collect();
+        Self { data, len }
+    }
+}
+
+impl<T, const B: usize> From<StaticBTreeSet<T, B>> for BinarySet<T> {
+    fn from(set: StaticBTreeSet<T, B>) -> Self {
+        let slots = Slots::<B>::new(set.node_count(), set.len);
+        let mut data: Vec<Option<T>> = set.data.into_iter().map(Some).collect();
+        let sorted = slots.map(|slot| data[slot].take().unwrap()).collect();
+        BinarySet::from_sorted_vec_unchecked(sorted, Natural)
+    }
+}
+
+impl<T: NodeKey, const B: usize> FromIterator<T> for StaticBTreeSet<T, B> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        BinarySet::from_iter(iter).into()
+    }
+}
+
+impl<T, const B: usize> Default for StaticBTreeSet<T, B> {
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a StaticBTreeSet<T, B> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, const B: usize> Collection for StaticBTreeSet<T, B> {
+    type Item = T;
+}
+
+impl<T, const B: usize> CollectionRef for StaticBTreeSet<T, B> {
+    type ItemRef<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    covariant_item_ref!();
+}
+
+impl<T, const B: usize> SimpleCollectionRef for StaticBTreeSet<T, B> {
+    simple_collection_ref!();
+}
+
+impl<T, const B: usize> Len for StaticBTreeSet<T, B> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T: NodeKey, const B: usize> Get<&'a T> for StaticBTreeSet<T, B> {
+    fn get(&self, key: &'a T) -> Option<Self::ItemRef<'_>> {
+        let slot = self.lower_bound(key)?;
+        let x = &self.data[slot];
+        (x == key).then_some(x)
+    }
+}
+
+impl<T, const B: usize> cc_traits::Iter for StaticBTreeSet<T, B> {
+    type Iter<'a>
+        = Iter<'a, T, B>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+/// The first `len` slots in order.
+#[derive(Debug, Clone)]
+struct Slots<const B: usize> {
+    nodes: usize,
+    /// The node and key index of the next slot.
+    next: (usize, usize),
+    len: usize,
+}
+
+impl<const B: usize> Slots<B> {
+    fn new(nodes: usize, len: usize) -> Self {
+        let mut slots = Self {
+            nodes,
+            next: (0, 0),
+            len,
+        };
+        slots.next = (slots.leftmost(0), 0);
+        slots
+    }
+
+    /// The leftmost node of the subtree rooted at node `k`.
+    fn leftmost(&self, mut k: usize) -> usize {
+        while child::<B>(k, 0) < self.nodes {
+            k = child::<B>(k, 0);
+        }
+        k
+    }
+
+    /// The slot after key `i` of node `k` in order: the first of the subtree to its right,
+    /// the next key of the node, or the key above the nearest ancestor that is not a last child.
+    fn successor(&self, k: usize, i: usize) -> (usize, usize) {
+        let right = child::<B>(k, i + 1);
+        if right < self.nodes {
+            return (self.leftmost(right), 0);
+        }
+        if i + 1 < B {
+            return (k, i + 1);
+        }
+        let mut k = k;
+        while k > 0 {
+            let (parent, index) = ((k - 1) / (B + 1), (k - 1) % (B + 1));
+            if index < B {
+                return (parent, index);
+            }
+            k = parent;
+        }
+        (self.nodes, 0)
+    }
+}
+
+impl<const B: usize> Iterator for Slots<B> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.len = self.len.checked_sub(1)?;
+        let (k, i) = self.next;
+        if self.len > 0 {
+            self.next = self.successor(k, i);
+        }
+        Some(k * B + i)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+/// In-order iterator over a [`StaticBTreeSet`], returned by `iter`.
+#[derive(Debug)]
+pub struct Iter<'a, T, const B: usize> {
+    data: &'a [T],
+    slots: Slots<B>,
+}
+
+impl<T, const B: usize> Clone for Iter<'_, T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data,
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+impl<'a, T, const B: usize> Iterator for Iter<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.slots.next()?;
+        Some(&self.data[slot])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.slots.size_hint()
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for Iter<'_, T, B> {}
+impl<T, const B: usize> FusedIterator for Iter<'_, T, B> {}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::static_btree::*;
+
+    fn check_count_less<T: NodeKey + Copy + std::fmt::Debug>(values: &[T]) {
+        let mut node = values.to_vec();
+        node.sort();
+        for key in values {
+            for len in 0..=node.len() {
+                let expected = node[..len].iter().filter(|x| *x < key).count();
+                assert_eq!(
+                    T::count_less(&node[..len], key),
+                    expected,
+                    "{key:?} in {node:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn count_less() {
+        check_count_less(&[i8::MIN, -3, -1, 0, 1, 5, 7, i8::MAX]);
+        check_count_less(&[0u8, 1, 2, 127, 128, 200, u8::MAX]);
+        check_count_less(&[i16::MIN, -300, 0, 300, i16::MAX]);
+        check_count_less(&[0u16, 1, 32767, 32768, u16::MAX]);
+        check_count_less(&[i32::MIN, -5, 0, 3, 4, 9, 100, i32::MAX]);
+        check_count_less(&[0u32, 1, 2, 1 << 31, (1 << 31) + 1, u32::MAX]);
+        check_count_less(&[i64::MIN, -1, 0, 1, i64::MAX]);
+        check_count_less(&[0u64, 1, 1 << 63, u64::MAX]);
+        check_count_less(&[0usize, 1, usize::MAX / 2 + 1, usize::MAX]);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    pub fn count_less_64_matches_scalar() {
+        if !std::arch::is_x86_feature_detected!("sse4.2") {
+            return;
+        }
+        let boundary = 1u64 << 63;
+        let mut node: Vec<u64> = (0..5).map(|i| boundary - 2 + i).collect();
+        node.extend([0, 1, boundary / 2, u64::MAX - 1, u64::MAX]);
+        node.sort();
+        for key in node
+            .iter()
+            .flat_map(|x| [x.wrapping_sub(1), *x, x.wrapping_add(1)])
+        {
+            for len in 0..=node.len() {
+                let node = &node[..len];
+                let scalar = node.iter().filter(|x| **x < key).count();
+                // SAFETY: SSE4.2 is available.
+                let simd = unsafe { simd::count_less_64(node, key, key as i64, i64::MIN) };
+                assert_eq!(simd, scalar, "{key} in {node:?}");
+                assert_eq!(u64::count_less(node, &key), scalar);
+                let usizes: Vec<usize> = node.iter().map(|x| *x as usize).collect();
+                assert_eq!(usize::count_less(&usizes, &(key as usize)), scalar);
+            }
+        }
+    }
+
+    fn in_order<const B: usize>(nodes: usize, k: usize, slots: &mut Vec<usize>) {
+        if k < nodes {
+            for i in 0..B {
+                in_order::<B>(nodes, child::<B>(k, i), slots);
+                slots.push(k * B + i);
+            }
+            in_order::<B>(nodes, child::<B>(k, B), slots);
+        }
+    }
+
+    fn check_slots<const B: usize>() {
+        for nodes in 0..60 {
+            let mut expected = Vec::new();
+            in_order::<B>(nodes, 0, &mut expected);
+            for len in [0, nodes * B / 2, nodes * B] {
+                let slots: Vec<usize> = Slots::<B>::new(nodes, len).collect();
+                assert_eq!(slots, expected[..len], "{nodes} nodes of {B}");
+            }
+        }
+    }
+
+    #[test]
+    pub fn slots_follow_key_order() {
+        check_slots::<1>();
+        check_slots::<2>();
+        check_slots::<3>();
+        check_slots::<16>();
+    }
+
+    #[test]
+    pub fn get() {
+        for n in 0..200u32 {
+            let set: StaticBTreeSet<u32, 4> = StaticBTreeSet::from_iter((0..n).map(|x| x * 2));
+            assert_eq!(set.len(), n as usize);
+            for x in 0..(n * 2 + 2) {
+                let expected = (x % 2 == 0 && x < n * 2).then_some(&x);
+                assert_eq!(set.get(&x), expected);
+            }
+        }
+    }
+
+    #[test]
+    pub fn iter_and_round_trip() {
+        for n in 0..100u64 {
+            let binary = BinarySet::from_iter((0..n).map(|x| x * 3));
+            let set: StaticBTreeSet<u64, 3> = StaticBTreeSet::from(binary.clone());
+            assert!(set.iter().eq(&binary));
+            assert_eq!(set.iter().len(), n as usize);
+            assert_eq!(BinarySet::from(set), binary);
+        }
+    }
+
+    #[test]
+    pub fn scalar_keys() {
+        let set: StaticBTreeSet<String> =
+            StaticBTreeSet::from_iter(["b", "d", "a", "c"].map(String::from));
+        assert_eq!(set.get(&"c".to_string()), Some(&"c".to_string()));
+        assert_eq!(set.get(&"e".to_string()), None);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec!["a", "b", "c", "d"]);
+    }
+}
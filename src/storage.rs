@@ -0,0 +1,484 @@
+//! Backing stores for the sorted collections.
+//!
+//! A [`SortedStorage`] picks the [`Buffer`] a collection keeps its elements in. [`VecStore`] uses a
+//! `Vec`, and [`SmallStore`] keeps up to `N` elements inline before spilling to the heap, so
+//! collections that stay small never allocate.
+//!
+//! Merges run on a `Vec` when the elements are in one. While a small buffer is inline, elements
+//! are merged in one at a time, and anything that only removes elements compacts the buffer in
+//! place, so neither allocates until the buffer spills.
+
+use std::fmt;
+use std::iter::FusedIterator;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::vec;
+
+use crate::buffer::{self, ArrayBuffer, Retain};
+
+/// Picks the buffer that a sorted collection stores its elements in.
+pub trait SortedStorage {
+    type Buffer<T>: Buffer<T>;
+}
+
+/// A growable, contiguous buffer of elements.
+pub trait Buffer<T>:
+    Default
+    + Deref<Target = [T]>
+    + DerefMut
+    + FromIterator<T>
+    + Extend<T>
+    + From<Vec<T>>
+    + Into<Vec<T>>
+    + IntoIterator<Item = T, IntoIter: DoubleEndedIterator + ExactSizeIterator>
+{
+    fn with_capacity(capacity: usize) -> Self;
+
+    fn capacity(&self) -> usize;
+
+    fn reserve(&mut self, additional: usize);
+
+    /// Inserts `element` at `index`, shifting the elements after it up.
+    fn insert(&mut self, index: usize, element: T);
+
+    /// Removes the element at `index`, shifting the elements after it down.
+    fn remove(&mut self, index: usize) -> T;
+
+    fn push(&mut self, element: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    fn truncate(&mut self, len: usize);
+
+    /// Moves the elements from `at` onwards into a new buffer.
+    fn split_off(&mut self, at: usize) -> Self;
+
+    /// The elements as a `Vec`, if that is where they are stored.
+    fn as_vec_mut(&mut self) -> Option<&mut Vec<T>>;
+
+    /// Runs `f` on the elements as a `Vec`. A buffer that is not a `Vec` moves its elements into
+    /// one and back, and is left empty if `f` panics.
+    fn with_vec<R>(&mut self, f: impl FnOnce(&mut Vec<T>) -> R) -> R;
+
+    /// Keeps only the elements for which `keep` returns true, in order.
+    fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        Retain::new(self, Self::truncate).run(keep)
+    }
+
+    /// Removes each element for which `same` returns true when called with it and the last
+    /// element kept before it, as in `Vec::dedup_by`.
+    fn dedup_by(&mut self, mut same: impl FnMut(&T, &T) -> bool) {
+        let mut kept = self.len().min(1);
+        for read in 1..self.len() {
+            if !same(&self[read], &self[kept - 1]) {
+                self.swap(kept, read);
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
+    }
+}
+
+/// Stores elements in a `Vec`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VecStore;
+
+impl SortedStorage for VecStore {
+    type Buffer<T> = Vec<T>;
+}
+
+impl<T> Buffer<T> for Vec<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.reserve(additional)
+    }
+
+    fn insert(&mut self, index: usize, element: T) {
+        self.insert(index, element)
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        self.remove(index)
+    }
+
+    fn push(&mut self, element: T) {
+        self.push(element)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.truncate(len)
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        self.split_off(at)
+    }
+
+    fn as_vec_mut(&mut self) -> Option<&mut Vec<T>> {
+        Some(self)
+    }
+
+    fn with_vec<R>(&mut self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        f(self)
+    }
+
+    fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        self.retain(keep)
+    }
+
+    fn dedup_by(&mut self, mut same: impl FnMut(&T, &T) -> bool) {
+        self.dedup_by(|a, b| same(a, b))
+    }
+}
+
+/// Stores up to `N` elements inline, and spills to a `Vec` beyond that.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SmallStore<const N: usize>;
+
+impl<const N: usize> SortedStorage for SmallStore<N> {
+    type Buffer<T> = SmallBuffer<T, N>;
+}
+
+/// A buffer that keeps up to `N` elements inline before moving them all to the heap.
+/// Once spilled it stays on the heap, except when rebuilt from a `Vec` short enough to fit.
+pub struct SmallBuffer<T, const N: usize> {
+    inner: Inner<T, N>,
+}
+
+enum Inner<T, const N: usize> {
+    Inline(ArrayBuffer<T, N>),
+    Heap(Vec<T>),
+}
+
+/// Moves the elements of `inline` into a `Vec` with room for `additional` more.
+fn spill<T, const N: usize>(inline: ArrayBuffer<T, N>, additional: usize) -> Vec<T> {
+    let mut vec = Vec::with_capacity(inline.len() + additional);
+    vec.extend(inline);
+    vec
+}
+
+impl<T, const N: usize> SmallBuffer<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            inner: Inner::Inline(ArrayBuffer::new()),
+        }
+    }
+
+    /// Whether the elements have moved to the heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.inner, Inner::Heap(_))
+    }
+
+    /// Moves the elements to the heap if `additional` more would not fit inline.
+    fn make_room(&mut self, additional: usize) -> Option<&mut Vec<T>> {
+        if let Inner::Inline(inline) = &mut self.inner {
+            if inline.len() + additional <= N {
+                return None;
+            }
+            // at least double, so that pushing one at a time stays amortized
+            let additional = additional.max(inline.len().max(1));
+            self.inner = Inner::Heap(spill(mem::take(inline), additional));
+        }
+        match &mut self.inner {
+            Inner::Heap(vec) => Some(vec),
+            Inner::Inline(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for SmallBuffer<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match &self.inner {
+            Inner::Inline(inline) => inline,
+            Inner::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallBuffer<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match &mut self.inner {
+            Inner::Inline(inline) => inline,
+            Inner::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallBuffer<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallBuffer<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize> Buffer<T> for SmallBuffer<T, N> {
+    fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::new()
+        } else {
+            Self {
+                inner: Inner::Heap(Vec::with_capacity(capacity)),
+            }
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        match &self.inner {
+            Inner::Inline(_) => N,
+            Inner::Heap(vec) => vec.capacity(),
+        }
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        if let Some(vec) = self.make_room(additional) {
+            vec.reserve(additional);
+        }
+    }
+
+    fn insert(&mut self, index: usize, element: T) {
+        assert!(index <= self.len(), "insertion index is out of bounds");
+        if let Some(vec) = self.make_room(1) {
+            return vec.insert(index, element);
+        }
+        let Inner::Inline(inline) = &mut self.inner else {
+            unreachable!()
+        };
+        if inline.try_insert(index, element).is_err() {
+            unreachable!("room was made for the element")
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len(), "removal index is out of bounds");
+        match &mut self.inner {
+            Inner::Inline(inline) => inline.remove(index),
+            Inner::Heap(vec) => vec.remove(index),
+        }
+    }
+
+    fn push(&mut self, element: T) {
+        let len = self.len();
+        self.insert(len, element)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        match &mut self.inner {
+            Inner::Inline(inline) => inline.pop(),
+            Inner::Heap(vec) => vec.pop(),
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        match &mut self.inner {
+            Inner::Inline(inline) => inline.truncate(len),
+            Inner::Heap(vec) => vec.truncate(len),
+        }
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "split index is out of bounds");
+        match &mut self.inner {
+            Inner::Inline(inline) => Self {
+                inner: Inner::Inline(inline.split_off(at)),
+            },
+            Inner::Heap(vec) => Self::from(vec.split_off(at)),
+        }
+    }
+
+    fn as_vec_mut(&mut self) -> Option<&mut Vec<T>> {
+        match &mut self.inner {
+            Inner::Inline(_) => None,
+            Inner::Heap(vec) => Some(vec),
+        }
+    }
+
+    fn with_vec<R>(&mut self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        match &mut self.inner {
+            Inner::Inline(inline) => {
+                let mut vec = spill(mem::take(inline), 0);
+                let result = f(&mut vec);
+                *self = Self::from(vec);
+                result
+            }
+            Inner::Heap(vec) => f(vec),
+        }
+    }
+}
+
+impl<T, const N: usize> From<Vec<T>> for SmallBuffer<T, N> {
+    /// Moves the elements inline if they fit, freeing the `Vec`.
+    fn from(vec: Vec<T>) -> Self {
+        let inner = if vec.len() > N {
+            Inner::Heap(vec)
+        } else {
+            Inner::Inline(vec.into_iter().collect())
+        };
+        Self { inner }
+    }
+}
+
+impl<T, const N: usize> From<SmallBuffer<T, N>> for Vec<T> {
+    fn from(buffer: SmallBuffer<T, N>) -> Self {
+        match buffer.inner {
+            Inner::Inline(inline) => spill(inline, 0),
+            Inner::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallBuffer<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut buffer = Self::new();
+        buffer.extend(iter);
+        buffer
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallBuffer<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for element in iter {
+            self.push(element);
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallBuffer<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let inner = match self.inner {
+            Inner::Inline(inline) => IntoIterInner::Inline(inline.into_iter()),
+            Inner::Heap(vec) => IntoIterInner::Heap(vec.into_iter()),
+        };
+        IntoIter { inner }
+    }
+}
+
+/// Owning iterator over a [`SmallBuffer`].
+pub struct IntoIter<T, const N: usize> {
+    inner: IntoIterInner<T, N>,
+}
+
+enum IntoIterInner<T, const N: usize> {
+    Inline(buffer::IntoIter<T, N>),
+    Heap(vec::IntoIter<T>),
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match &mut self.inner {
+            IntoIterInner::Inline(iter) => iter.next(),
+            IntoIterInner::Heap(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IntoIterInner::Inline(iter) => iter.size_hint(),
+            IntoIterInner::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        match &mut self.inner {
+            IntoIterInner::Inline(iter) => iter.next_back(),
+            IntoIterInner::Heap(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for IntoIter<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIter")
+            .field("remaining", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::storage::*;
+    use crate::test_support::Counted;
+
+    #[test]
+    pub fn small_buffer_spills() {
+        let mut buffer: SmallBuffer<u32, 4> = SmallBuffer::new();
+        for x in [3, 1, 2] {
+            buffer.push(x);
+        }
+        buffer.insert(0, 0);
+        assert!(!buffer.spilled());
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(&*buffer, &[0, 3, 1, 2]);
+
+        buffer.insert(2, 9);
+        assert!(buffer.spilled());
+        assert_eq!(&*buffer, &[0, 3, 9, 1, 2]);
+        assert_eq!(buffer.remove(1), 3);
+        assert_eq!(buffer.pop(), Some(2));
+
+        let mut small = SmallBuffer::<u32, 4>::from(Vec::from(buffer));
+        assert!(!small.spilled());
+        assert_eq!(small.remove(0), 0);
+        assert_eq!(&*small.split_off(1), &[1]);
+        assert_eq!(&*small, &[9]);
+        small.with_vec(|vec| vec.extend([10, 11, 12, 13]));
+        assert!(small.spilled());
+        small.retain(|x| x % 2 == 1);
+        assert_eq!(&*small, &[9, 11, 13]);
+    }
+
+    #[test]
+    pub fn spilling_drops_each_element_once() {
+        let drops = Rc::new(Cell::new(0));
+        let counted = |x| Counted(x, drops.clone());
+
+        let mut buffer: SmallBuffer<Counted, 2> = (0..2).map(counted).collect();
+        buffer.extend((2..4).map(counted));
+        assert!(buffer.spilled());
+        assert_eq!(drops.get(), 0);
+        let mut inline = SmallBuffer::<Counted, 2>::from(Vec::from(buffer.split_off(2)));
+        assert!(!inline.spilled());
+        drop(inline.pop());
+        assert_eq!(drops.get(), 1);
+        drop(buffer.into_iter().next_back());
+        drop(inline);
+        assert_eq!(drops.get(), 4);
+    }
+}
@@ -0,0 +1,89 @@
+//! Fixtures shared by the unit tests.
+
+use std::borrow::Borrow;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use cc_traits::Len;
+
+/// Counts its drops in a shared cell.
+#[derive(Debug, Clone)]
+pub struct Counted(pub u32, pub Rc<Cell<usize>>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+/// Ordered by its number. Panics when dropped if the flag is set; clones are not brittle.
+#[derive(Debug)]
+pub struct Brittle(pub u32, pub bool);
+
+impl Clone for Brittle {
+    fn clone(&self) -> Self {
+        Brittle(self.0, false)
+    }
+}
+
+impl Drop for Brittle {
+    fn drop(&mut self) {
+        if self.1 && !std::thread::panicking() {
+            panic!("dropped a brittle key");
+        }
+    }
+}
+
+impl Borrow<u32> for Brittle {
+    fn borrow(&self) -> &u32 {
+        &self.0
+    }
+}
+
+impl PartialEq for Brittle {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Brittle {}
+
+impl Ord for Brittle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Brittle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Runs `op`, checks that it panicked with `expected`, and that the map still pairs
+/// every key with the value `value_of` gives for it.
+pub fn assert_lockstep_after<M, K, V>(
+    map: &mut M,
+    op: impl FnOnce(&mut M),
+    value_of: impl Fn(&K) -> V,
+    expected: &str,
+) where
+    M: Len,
+    for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    V: PartialEq + Debug,
+{
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| op(map))).unwrap_err();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or_default();
+    assert!(message.contains(expected), "panicked with {message:?}");
+    assert_eq!(map.into_iter().count(), map.len());
+    for (key, value) in &*map {
+        assert_eq!(*value, value_of(key));
+    }
+}
@@ -0,0 +1,96 @@
+//! Checks that small collections stay off the heap. The counting allocator is installed for
+//! this test binary only.
+#![cfg(feature = "std")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use binary_tree_collections::map::SmallBinaryMap;
+use binary_tree_collections::pair_map::SmallBinaryPairMap;
+use binary_tree_collections::set::{BinarySet, ExtendStrategy, SmallBinarySet};
+use cc_traits::{Iter, Len, Reserve};
+
+/// Counts the allocations made on each thread, so that a test can check it made none.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn small_store_does_not_allocate() {
+    let before = allocations();
+
+    let mut set: SmallBinarySet<u32, 8> = [5, 1, 3].into_iter().collect();
+    set.extend([4, 2, 4]);
+    set.extend_with_merge([0, 6]);
+    set.union_with([7, 1].into_iter().collect());
+    set.intersect_with(&[1, 2, 4, 5, 7].into_iter().collect());
+    set.difference_with(&[2].into_iter().collect());
+    set.symmetric_difference_with([1, 3].into_iter().collect());
+    let mut other = [0, 4].into_iter().collect();
+    set.append(&mut other);
+    set.retain(|x| *x != 5);
+    assert!(set.iter().eq(&[0, 3, 4, 7]));
+    assert!(other.is_empty());
+
+    let mut map: SmallBinaryMap<u32, u32, 8> = [(3, 30), (1, 10)].into_iter().collect();
+    map.extend([(2, 20), (3, 31)]);
+    map.retain(|k, v| {
+        *v += 1;
+        k % 2 == 1
+    });
+    let mut other = [(0, 0), (3, 0)].into_iter().collect();
+    map.append(&mut other);
+    assert!(map.iter().eq([(&0, &0), (&1, &11), (&3, &0)]));
+
+    let mut pairs: SmallBinaryPairMap<u32, u32, 8> = [(3, 30), (1, 10)].into_iter().collect();
+    pairs.extend([(2, 20), (3, 31)]);
+    pairs.retain(|k, _| k % 2 == 1);
+    let mut other = [(0, 0), (3, 0)].into_iter().collect();
+    pairs.append(&mut other);
+    assert_eq!(pairs.as_slice(), &[(0, 0), (1, 10), (3, 0)]);
+
+    assert_eq!(allocations(), before);
+    assert!(!set.buffer().spilled() && !map.key_buffer().spilled());
+}
+
+#[test]
+fn extend_works_in_reserved_capacity() {
+    let batches: [&[u32]; 4] = [&[3, 1, 2], &[40, 45, 41], &[45, 5, 12, 5, 7], &[]];
+    let strategies = [
+        ExtendStrategy::Auto,
+        ExtendStrategy::Sort,
+        ExtendStrategy::Inserts,
+        ExtendStrategy::Rotates,
+        ExtendStrategy::Merge,
+    ];
+    for strategy in strategies {
+        let mut set = BinarySet::from_iter((10..40).step_by(2));
+        set.reserve(64);
+        let before = allocations();
+        for batch in batches {
+            set.extend_with_strategy(batch.iter().copied(), strategy);
+        }
+        assert_eq!(allocations(), before, "{strategy:?}");
+        assert_eq!(set.len(), 23);
+    }
+}